@@ -1,4 +1,1030 @@
 <?xml version="1.0" encoding="UTF-8"?>
-<tileset version="1.10" tiledversion="1.11.2" name="tileset" tilewidth="8" tileheight="8" tilecount="1024" columns="32">
- <image source="tileset.ase" width="256" height="256"/>
+<tileset version="1.10" tiledversion="1.11.2" name="tileset" tilewidth="8" tileheight="8" tilecount="2048" columns="32">
+ <image source="tileset.ase" width="256" height="512"/>
+ <tile id="0">
+  <properties>
+   <property name="kind" value="lasso_target"/>
+  </properties>
+ </tile>
+ <tile id="1">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="bandit"/>
+  </properties>
+ </tile>
+ <tile id="2">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="standing_bandit"/>
+  </properties>
+ </tile>
+ <tile id="3">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="demo_bandit"/>
+  </properties>
+ </tile>
+ <tile id="4">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="laser"/>
+  </properties>
+ </tile>
+ <tile id="5">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="bat"/>
+  </properties>
+ </tile>
+ <tile id="6">
+  <properties>
+   <property name="kind" value="enemy"/>
+   <property name="enemy" value="skeleton"/>
+  </properties>
+ </tile>
+ <tile id="7">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="8">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="9">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="10">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="11">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="12">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="13">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="14">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="15">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="16">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="17">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="18">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="19">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="20">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="21">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="22">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="23">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="24">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="25">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="26">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="27">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="28">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="29">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="30">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="31">
+  <properties>
+   <property name="kind" value="enemy"/>
+  </properties>
+ </tile>
+ <tile id="128">
+  <properties>
+   <property name="deadly" type="bool" value="true"/>
+   <property name="death_animation" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="256">
+  <properties>
+   <property name="animation_height" type="int" value="2"/>
+  </properties>
+  <animation>
+   <frame tileid="1800" duration="500"/>
+   <frame tileid="1801" duration="500"/>
+   <frame tileid="1802" duration="500"/>
+   <frame tileid="1803" duration="500"/>
+   <frame tileid="1804" duration="500"/>
+   <frame tileid="1805" duration="500"/>
+  </animation>
+ </tile>
+ <tile id="288">
+  <properties>
+   <property name="deadly" type="bool" value="true"/>
+   <property name="death_animation" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="320">
+  <properties>
+   <property name="animation_height" type="int" value="2"/>
+  </properties>
+  <animation>
+   <frame tileid="1792" duration="400"/>
+   <frame tileid="1793" duration="400"/>
+   <frame tileid="1794" duration="400"/>
+   <frame tileid="1795" duration="300"/>
+   <frame tileid="1796" duration="300"/>
+   <frame tileid="1797" duration="400"/>
+   <frame tileid="1798" duration="400"/>
+   <frame tileid="1799" duration="400"/>
+  </animation>
+ </tile>
+ <tile id="352">
+  <properties>
+   <property name="deadly" type="bool" value="true"/>
+   <property name="death_animation" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="384">
+  <properties>
+   <property name="kind" value="horse"/>
+  </properties>
+ </tile>
+ <tile id="416">
+  <properties>
+   <property name="kind" value="horse_arrow"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="417">
+  <properties>
+   <property name="kind" value="horse_arrow"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="418">
+  <properties>
+   <property name="kind" value="horse_stop"/>
+  </properties>
+ </tile>
+ <tile id="419">
+  <properties>
+   <property name="kind" value="horse_wait"/>
+  </properties>
+ </tile>
+ <tile id="448">
+  <properties>
+   <property name="kind" value="player_spawn"/>
+  </properties>
+ </tile>
+ <tile id="449">
+  <properties>
+   <property name="kind" value="level_end"/>
+  </properties>
+ </tile>
+ <tile id="450">
+  <properties>
+   <property name="kind" value="elevator_shaft"/>
+  </properties>
+ </tile>
+ <tile id="480">
+  <properties>
+   <property name="kind" value="enemy_path"/>
+  </properties>
+ </tile>
+ <tile id="481">
+  <properties>
+   <property name="kind" value="spawner"/>
+  </properties>
+ </tile>
+ <tile id="512">
+  <properties>
+   <property name="kind" value="climbable"/>
+  </properties>
+ </tile>
+ <tile id="513">
+  <properties>
+   <property name="kind" value="climbable"/>
+  </properties>
+ </tile>
+ <tile id="608">
+  <properties>
+   <property name="kind" value="trigger"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="609">
+  <properties>
+   <property name="kind" value="trigger"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="610">
+  <properties>
+   <property name="kind" value="trigger"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="611">
+  <properties>
+   <property name="kind" value="trigger"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="612">
+  <properties>
+   <property name="kind" value="trigger"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="640">
+  <properties>
+   <property name="kind" value="trigger_spawner"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="641">
+  <properties>
+   <property name="kind" value="trigger_spawner"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="642">
+  <properties>
+   <property name="kind" value="trigger_spawner"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="643">
+  <properties>
+   <property name="kind" value="trigger_spawner"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="644">
+  <properties>
+   <property name="kind" value="trigger_spawner"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="672">
+  <properties>
+   <property name="blocks_projectiles" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="704">
+  <properties>
+   <property name="kind" value="camera_reset"/>
+  </properties>
+ </tile>
+ <tile id="705">
+  <properties>
+   <property name="kind" value="camera_offset"/>
+  </properties>
+ </tile>
+ <tile id="864">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="896">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="897">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="898">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="899">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="900">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="901">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="5"/>
+  </properties>
+ </tile>
+ <tile id="902">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="6"/>
+  </properties>
+ </tile>
+ <tile id="903">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="7"/>
+  </properties>
+ </tile>
+ <tile id="904">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="8"/>
+  </properties>
+ </tile>
+ <tile id="905">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="9"/>
+  </properties>
+ </tile>
+ <tile id="906">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="10"/>
+  </properties>
+ </tile>
+ <tile id="907">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="11"/>
+  </properties>
+ </tile>
+ <tile id="908">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="12"/>
+  </properties>
+ </tile>
+ <tile id="909">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="13"/>
+  </properties>
+ </tile>
+ <tile id="910">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="14"/>
+  </properties>
+ </tile>
+ <tile id="911">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="15"/>
+  </properties>
+ </tile>
+ <tile id="912">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="16"/>
+  </properties>
+ </tile>
+ <tile id="913">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="17"/>
+  </properties>
+ </tile>
+ <tile id="914">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="18"/>
+  </properties>
+ </tile>
+ <tile id="915">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="19"/>
+  </properties>
+ </tile>
+ <tile id="916">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="20"/>
+  </properties>
+ </tile>
+ <tile id="917">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="21"/>
+  </properties>
+ </tile>
+ <tile id="918">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="22"/>
+  </properties>
+ </tile>
+ <tile id="919">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="23"/>
+  </properties>
+ </tile>
+ <tile id="920">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="24"/>
+  </properties>
+ </tile>
+ <tile id="921">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="25"/>
+  </properties>
+ </tile>
+ <tile id="922">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="26"/>
+  </properties>
+ </tile>
+ <tile id="923">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="27"/>
+  </properties>
+ </tile>
+ <tile id="924">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="28"/>
+  </properties>
+ </tile>
+ <tile id="925">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="29"/>
+  </properties>
+ </tile>
+ <tile id="926">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="30"/>
+  </properties>
+ </tile>
+ <tile id="927">
+  <properties>
+   <property name="kind" value="marker"/>
+   <property name="value" type="int" value="31"/>
+  </properties>
+ </tile>
+ <tile id="928">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="929">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="930">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="931">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="932">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="933">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="5"/>
+  </properties>
+ </tile>
+ <tile id="934">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="6"/>
+  </properties>
+ </tile>
+ <tile id="935">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="7"/>
+  </properties>
+ </tile>
+ <tile id="936">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="8"/>
+  </properties>
+ </tile>
+ <tile id="937">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="9"/>
+  </properties>
+ </tile>
+ <tile id="938">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="10"/>
+  </properties>
+ </tile>
+ <tile id="939">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="11"/>
+  </properties>
+ </tile>
+ <tile id="940">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="12"/>
+  </properties>
+ </tile>
+ <tile id="941">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="13"/>
+  </properties>
+ </tile>
+ <tile id="942">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="14"/>
+  </properties>
+ </tile>
+ <tile id="943">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="15"/>
+  </properties>
+ </tile>
+ <tile id="944">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="16"/>
+  </properties>
+ </tile>
+ <tile id="945">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="17"/>
+  </properties>
+ </tile>
+ <tile id="946">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="18"/>
+  </properties>
+ </tile>
+ <tile id="947">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="19"/>
+  </properties>
+ </tile>
+ <tile id="948">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="20"/>
+  </properties>
+ </tile>
+ <tile id="949">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="21"/>
+  </properties>
+ </tile>
+ <tile id="950">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="22"/>
+  </properties>
+ </tile>
+ <tile id="951">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="23"/>
+  </properties>
+ </tile>
+ <tile id="952">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="24"/>
+  </properties>
+ </tile>
+ <tile id="953">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="25"/>
+  </properties>
+ </tile>
+ <tile id="954">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="26"/>
+  </properties>
+ </tile>
+ <tile id="955">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="27"/>
+  </properties>
+ </tile>
+ <tile id="956">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="28"/>
+  </properties>
+ </tile>
+ <tile id="957">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="29"/>
+  </properties>
+ </tile>
+ <tile id="958">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="30"/>
+  </properties>
+ </tile>
+ <tile id="959">
+  <properties>
+   <property name="kind" value="boss"/>
+   <property name="value" type="int" value="31"/>
+  </properties>
+ </tile>
+ <tile id="960">
+  <properties>
+   <property name="kind" value="half"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="961">
+  <properties>
+   <property name="kind" value="half"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="962">
+  <properties>
+   <property name="kind" value="half"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="963">
+  <properties>
+   <property name="kind" value="half"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="964">
+  <properties>
+   <property name="kind" value="half"/>
+   <property name="value" type="int" value="5"/>
+  </properties>
+ </tile>
+ <tile id="992">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="993">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="994">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="3"/>
+  </properties>
+ </tile>
+ <tile id="995">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="4"/>
+  </properties>
+ </tile>
+ <tile id="996">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="5"/>
+  </properties>
+ </tile>
+ <tile id="997">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="6"/>
+  </properties>
+ </tile>
+ <tile id="998">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="7"/>
+  </properties>
+ </tile>
+ <tile id="999">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="8"/>
+  </properties>
+ </tile>
+ <tile id="1000">
+  <properties>
+   <property name="kind" value="number"/>
+   <property name="value" type="int" value="9"/>
+  </properties>
+ </tile>
+ <tile id="1024">
+  <properties>
+   <property name="kind" value="fog"/>
+  </properties>
+ </tile>
+ <tile id="1856">
+  <properties>
+   <property name="collision" value="top_half"/>
+  </properties>
+ </tile>
+ <tile id="1857">
+  <properties>
+   <property name="collision" value="bottom_half"/>
+  </properties>
+ </tile>
+ <tile id="1858">
+  <properties>
+   <property name="collision" value="slope_up_right"/>
+  </properties>
+ </tile>
+ <tile id="1859">
+  <properties>
+   <property name="collision" value="slope_up_left"/>
+  </properties>
+ </tile>
+ <tile id="1860">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_low"/>
+  </properties>
+ </tile>
+ <tile id="1861">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_high"/>
+  </properties>
+ </tile>
+ <tile id="1862">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_low"/>
+  </properties>
+ </tile>
+ <tile id="1863">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_high"/>
+  </properties>
+ </tile>
+ <tile id="1864">
+  <properties>
+   <property name="collision" value="top_half"/>
+  </properties>
+ </tile>
+ <tile id="1865">
+  <properties>
+   <property name="collision" value="bottom_half"/>
+  </properties>
+ </tile>
+ <tile id="1866">
+  <properties>
+   <property name="collision" value="slope_up_right"/>
+  </properties>
+ </tile>
+ <tile id="1867">
+  <properties>
+   <property name="collision" value="slope_up_left"/>
+  </properties>
+ </tile>
+ <tile id="1868">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_low"/>
+  </properties>
+ </tile>
+ <tile id="1869">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_high"/>
+  </properties>
+ </tile>
+ <tile id="1870">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_low"/>
+  </properties>
+ </tile>
+ <tile id="1871">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_high"/>
+  </properties>
+ </tile>
+ <tile id="1872">
+  <properties>
+   <property name="collision" value="top_half"/>
+  </properties>
+ </tile>
+ <tile id="1873">
+  <properties>
+   <property name="collision" value="bottom_half"/>
+  </properties>
+ </tile>
+ <tile id="1874">
+  <properties>
+   <property name="collision" value="slope_up_right"/>
+  </properties>
+ </tile>
+ <tile id="1875">
+  <properties>
+   <property name="collision" value="slope_up_left"/>
+  </properties>
+ </tile>
+ <tile id="1876">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_low"/>
+  </properties>
+ </tile>
+ <tile id="1877">
+  <properties>
+   <property name="collision" value="shallow_slope_up_right_high"/>
+  </properties>
+ </tile>
+ <tile id="1878">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_low"/>
+  </properties>
+ </tile>
+ <tile id="1879">
+  <properties>
+   <property name="collision" value="shallow_slope_up_left_high"/>
+  </properties>
+ </tile>
+ <tile id="1880">
+  <properties>
+   <property name="breakable" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="1881">
+  <properties>
+   <property name="breakable" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="1882">
+  <properties>
+   <property name="breakable" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="1888">
+  <properties>
+   <property name="surface" value="ice"/>
+  </properties>
+ </tile>
+ <tile id="1889">
+  <properties>
+   <property name="surface" value="conveyor_right"/>
+  </properties>
+  <animation>
+   <frame tileid="1889" duration="100"/>
+   <frame tileid="1890" duration="100"/>
+   <frame tileid="1891" duration="100"/>
+   <frame tileid="1892" duration="100"/>
+  </animation>
+ </tile>
+ <tile id="1893">
+  <properties>
+   <property name="surface" value="conveyor_left"/>
+  </properties>
+  <animation>
+   <frame tileid="1892" duration="100"/>
+   <frame tileid="1891" duration="100"/>
+   <frame tileid="1890" duration="100"/>
+   <frame tileid="1889" duration="100"/>
+  </animation>
+ </tile>
+ <tile id="1894">
+  <properties>
+   <property name="surface" value="spring"/>
+  </properties>
+ </tile>
+ <tile id="1897">
+  <properties>
+   <property name="kind" value="wind"/>
+   <property name="value" type="int" value="0"/>
+  </properties>
+ </tile>
+ <tile id="1898">
+  <properties>
+   <property name="kind" value="wind"/>
+   <property name="value" type="int" value="1"/>
+  </properties>
+ </tile>
+ <tile id="1899">
+  <properties>
+   <property name="kind" value="quicksand"/>
+  </properties>
+ </tile>
+ <tile id="1900">
+  <properties>
+   <property name="collision" value="platform"/>
+  </properties>
+ </tile>
+ <tile id="1901">
+  <properties>
+   <property name="kind" value="moving_platform"/>
+  </properties>
+ </tile>
+ <tile id="1902">
+  <properties>
+   <property name="kind" value="moving_platform"/>
+  </properties>
+ </tile>
 </tileset>
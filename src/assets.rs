@@ -1,17 +1,107 @@
-use std::{
-    collections::{HashMap, HashSet},
-    f32::consts::PI,
-};
+use std::{borrow::Cow, collections::HashMap, ops::Deref, path::Path, sync::RwLock};
 
 use asefile::AsepriteFile;
 use image::EncodableLayout;
 use include_dir::{Dir, include_dir};
 use macroquad::prelude::*;
 
-use crate::{
-    enemies::{ENEMIES, EnemySpawner, LevelEnemyData},
-    utils::{DEBUG_FLAGS, create_camera},
-};
+use crate::utils::DEBUG_FLAGS;
+
+pub use hot_reload::*;
+pub use level::*;
+pub use manifest::*;
+pub use tiles::*;
+
+mod hot_reload;
+mod level;
+mod manifest;
+mod tiles;
+
+pub static LEVELS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/levels");
+static ASSETS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/assets");
+
+/// Gets the contents of a file in `assets/`. Embedded in the binary, unless the reload flag is set.
+macro_rules! asset {
+    ($name:literal) => {
+        $crate::assets::read_named_asset($name).expect(concat!("missing asset ", $name))
+    };
+}
+
+pub(crate) use asset;
+
+/// Gets the contents of a file in `assets/` named at runtime, like the sprites named by `enemies.toml`.
+/// Embedded in the binary, unless the reload flag is set, in which case files added since the build can be read too.
+pub fn read_named_asset(name: &str) -> Option<Cow<'static, [u8]>> {
+    if DEBUG_FLAGS.reload {
+        match std::fs::read(Path::new(ASSETS_PATH).join(name)) {
+            Ok(bytes) => return Some(Cow::Owned(bytes)),
+            Err(error) => warn!("couldn't read {name} from disk, using embedded copy: {error}"),
+        }
+    }
+    ASSETS_DIR
+        .get_file(name)
+        .map(|f| Cow::Borrowed(f.contents()))
+}
+
+/// Reads a file in `levels/`. Embedded in the binary, unless the reload flag is set.
+pub fn read_level_file(name: &str) -> Option<String> {
+    if DEBUG_FLAGS.reload {
+        return std::fs::read_to_string(Path::new(LEVELS_PATH).join(name)).ok();
+    }
+    LEVELS_DIR
+        .get_file(name)
+        .and_then(|f| f.contents_utf8())
+        .map(str::to_string)
+}
+
+/// A table read from a file in `assets/` the first time it's used, which hot reloading can read again.
+/// Replaced tables are leaked, so anything still pointing into them, like enemies of the level being left, stays valid.
+pub struct Reloadable<T: 'static> {
+    current: RwLock<Option<&'static T>>,
+    /// The file the table is read from, for errors
+    file: &'static str,
+    read: fn() -> Result<T, String>,
+}
+impl<T> Reloadable<T> {
+    pub const fn new(file: &'static str, read: fn() -> Result<T, String>) -> Self {
+        Self {
+            current: RwLock::new(None),
+            file,
+            read,
+        }
+    }
+    /// Reads the table's file again, without replacing the table
+    pub fn read(&self) -> Result<T, String> {
+        (self.read)().map_err(|error| format!("couldn't load {}: {error}", self.file))
+    }
+    /// Replaces the table with one read with [Reloadable::read], returning the one it replaced
+    pub fn replace(&self, table: T) -> Option<&'static T> {
+        self.current
+            .write()
+            .unwrap()
+            .replace(Box::leak(Box::new(table)))
+    }
+    /// Puts back a table returned by [Reloadable::replace]
+    pub fn restore(&self, table: Option<&'static T>) {
+        *self.current.write().unwrap() = table;
+    }
+}
+impl<T> Deref for Reloadable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        if let Some(table) = *self.current.read().unwrap() {
+            return table;
+        }
+        let mut current = self.current.write().unwrap();
+        // another thread may have read it while this one waited
+        current.get_or_insert_with(|| {
+            Box::leak(Box::new(
+                self.read().unwrap_or_else(|error| panic!("{error}")),
+            ))
+        })
+    }
+}
 
 pub struct Assets {
     pub tileset: Spritesheet,
@@ -25,12 +115,12 @@ pub struct Assets {
     pub elevator: AnimationsGroup,
     pub doors: AnimationsGroup,
     pub levels: Vec<Level>,
+    pub worlds: Vec<World>,
     pub projectiles: AnimationsGroup,
     pub horse: AnimationsGroup,
     pub blood: Animation,
     pub die: AnimationsGroup,
     pub target: Animation,
-    pub animated_tiles: Vec<Animation>,
     pub dialogue: Texture2D,
     pub pole: Animation,
     pub font: Font,
@@ -47,621 +137,67 @@ pub struct Assets {
     pub henry_dust: Animation,
 }
 impl Assets {
-    pub fn load() -> Self {
-        let tileset = Spritesheet::new(
-            load_ase_texture(include_bytes!("../assets/tileset.ase"), None),
-            8.0,
-        );
+    /// Loads all assets. If any levels fail to load, every error is returned instead of only the first.
+    pub fn load() -> Result<Self, Vec<LevelLoadError>> {
+        let tileset = Spritesheet::new(load_ase_texture(&asset!("tileset.ase"), None), 8.0);
 
-        let mut levels = Vec::new();
-        static LEVELS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/levels");
+        let (worlds, levels) = load_tower(read_level_file)?;
         for file in LEVELS_DIR.files() {
-            let name = file
-                .path()
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .to_string();
-
-            let level = Level::load(file.contents_utf8().unwrap(), &tileset, name);
-            levels.push(level);
-        }
-        info!("loaded {} levels", levels.len());
-        Self {
-            levels,
-            portraits: Spritesheet::new(
-                load_ase_texture(include_bytes!("../assets/portraits.ase"), None),
-                22.0,
-            ),
-            boss_badges: Spritesheet::new(
-                load_ase_texture(include_bytes!("../assets/boss_badges.ase"), None),
-                10.0,
-            ),
-            get_badge: Animation::from_file(include_bytes!("../assets/get_badge.ase")),
-            font: load_ttf_font_from_bytes(include_bytes!("../assets/font.ttf")).unwrap(),
-            clouds: Animation::from_file(include_bytes!("../assets/clouds.ase")),
-            torso: AnimationsGroup::from_file(include_bytes!("../assets/torso.ase")),
-            legs: AnimationsGroup::from_file(include_bytes!("../assets/legs.ase")),
-            elevator: AnimationsGroup::from_file(include_bytes!("../assets/elevator.ase")),
-            doors: AnimationsGroup::from_file(include_bytes!("../assets/doors.ase")),
-            projectiles: AnimationsGroup::from_file(include_bytes!("../assets/projectiles.ase")),
-            horse: AnimationsGroup::from_file(include_bytes!("../assets/horse.ase")),
-            blood: Animation::from_file(include_bytes!("../assets/blood.ase")),
-            die: AnimationsGroup::from_file(include_bytes!("../assets/die.ase")),
-            target: Animation::from_file(include_bytes!("../assets/target.ase")),
-            animated_tiles: vec![
-                Animation::from_file(include_bytes!("../assets/lava.ase")),
-                Animation::from_file(include_bytes!("../assets/blood_tile.ase")),
-            ],
-            dialogue: load_ase_texture(include_bytes!("../assets/dialogue.ase"), None),
-            pole: Animation::from_file(include_bytes!("../assets/pole.ase")),
-
-            fireking: AnimationsGroup::from_file(include_bytes!("../assets/fireking.ase")),
-            fireball: AnimationsGroup::from_file(include_bytes!("../assets/fireball.ase")),
-            fire_crown: Animation::from_file(include_bytes!("../assets/fire_crown.ase")),
-            lavafall: Animation::from_file(include_bytes!("../assets/lavafall.ase")),
-            fireking_target: Animation::from_file(include_bytes!("../assets/fireking_target.ase")),
-            fireking_pipe: load_ase_texture(include_bytes!("../assets/fireking_pipe.ase"), None),
-
-            henry: AnimationsGroup::from_file(include_bytes!("../assets/henry.ase")),
-            henry_target: load_ase_texture(include_bytes!("../assets/henry_target.ase"), None),
-            henry_dust: Animation::from_file(include_bytes!("../assets/henry_dust.ase")),
-            tileset,
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-pub struct Horse {
-    pub pos: Vec2,
-    pub home_pos: Vec2,
-    pub time: f32,
-    pub velocity: Vec2,
-    pub direction: Vec2,
-    pub flip: bool,
-    pub running: bool,
-    pub player_riding: bool,
-    pub returning_home: bool,
-}
-impl Horse {
-    pub fn is_flipped(&self) -> bool {
-        let mut flip = self.direction.x < 0.0;
-        if self.flip {
-            flip = !flip;
-        }
-        flip
-    }
-    pub fn get_normal(&self) -> Vec2 {
-        Vec2::from_angle(
-            self.direction.to_angle() - PI / 2.0 - if self.is_flipped() { PI } else { 0.0 },
-        )
-    }
-    pub fn new(pos: Vec2, direction: Vec2, flip: bool) -> Self {
-        Self {
-            pos,
-            direction,
-            flip,
-            home_pos: pos,
-            time: 0.0,
-            velocity: Vec2::ZERO,
-            running: false,
-            returning_home: false,
-            player_riding: false,
-        }
-    }
-}
-
-pub struct Level {
-    pub name: String,
-    pub data: Vec<[u16; 4]>,
-    pub width: usize,
-    pub enemies: Vec<LevelEnemyData>,
-    pub horses: Vec<Horse>,
-    pub boss: Option<(usize, Vec2)>,
-    pub camera: Camera2D,
-    pub min_pos: Vec2,
-    pub max_pos: Vec2,
-    pub player_spawn: Vec2,
-    pub enemy_paths: Vec<Vec<Vec2>>,
-    // The Y coordinate of the highest point/placed tile (lowest value)
-    pub roof_height: f32,
-    // The Y coordinate of the lowest point/placed tile (highest value)
-    pub floor_height: f32,
-    pub lasso_targets: Vec<Vec2>,
-    pub animated_tiles: Vec<(Vec2, usize)>,
-
-    pub camera_offsets: Vec<(Vec2, f32)>,
-
-    pub fog_points: Vec<Vec2>,
-
-    pub forced_player_spawn: Option<Vec2>,
-    pub forced_level_end: Option<Vec2>,
-    pub forced_level_elevator_shaft_height: Option<f32>,
-}
-impl Level {
-    pub fn get_world_index(&self) -> u32 {
-        self.name.chars().next().unwrap() as u32 - '0' as u32
-    }
-    pub fn find_marker(&self, marker_index: u16) -> Vec2 {
-        for (index, tile) in self.data.iter().enumerate() {
-            if tile[3] == 0 {
-                continue;
-            }
-            let tile = tile[3] - 1;
-            if tile == 896 + marker_index {
-                let pos = vec2(
-                    ((index % self.width) * 8) as f32 + self.min_pos.x,
-                    ((index / self.width) * 8) as f32 + self.min_pos.y,
-                );
-                return pos;
+            let name = file.path().to_string_lossy();
+            if name.ends_with(".tmx") && !levels.iter().any(|f| f.name == name) {
+                warn!("{name} isn't listed in {MANIFEST_FILE}, so won't be played");
             }
         }
-        panic!()
-    }
-    pub fn get_height(&self) -> f32 {
-        self.floor_height - self.roof_height + 8.0
-    }
-    pub fn get_tile(&self, x: i16, y: i16) -> [u16; 4] {
-        if (x as f32 * 8.0) < self.min_pos.x || ((x - 16) as f32 * 8.0) >= self.max_pos.x {
-            return [0, 1, 0, 0];
-        }
-        if (y as f32 * 8.0) < self.min_pos.y {
-            return [0; 4];
-        }
-        let x = (x - (self.min_pos.x / 8.0) as i16) as usize;
-        let y = (y - (self.min_pos.y / 8.0) as i16) as usize;
-        if x >= self.width || y >= self.data.len() / self.width {
-            return [0; 4];
-        }
-        self.data[x + y * self.width]
-    }
-    pub fn load(data: &str, tileset: &Spritesheet, name: String) -> Self {
-        let mut layers = data.split("<layer");
-        layers.next();
-        let layers_chunks: Vec<HashMap<(i16, i16), Chunk>> = layers.map(get_all_chunks).collect();
-        let mut min_x = i16::MAX;
-        let mut max_x = i16::MIN;
-        let mut min_y = i16::MAX;
-        let mut max_y = i16::MIN;
-        for chunk in &layers_chunks {
-            for (x, y) in chunk.keys() {
-                if *x < min_x {
-                    min_x = *x;
-                }
-                if *x > max_x {
-                    max_x = *x;
-                }
-                if *y < min_y {
-                    min_y = *y;
-                }
-                if *y > max_y {
-                    max_y = *y;
-                }
-            }
-        }
-        let width = max_x - min_x + 16;
-        let height = max_y - min_y + 16;
-
-        let mut data = vec![[0; 4]; (width * height) as usize];
-        let mut enemies = Vec::new();
-        let mut horses = Vec::new();
-        let mut lasso_targets = Vec::new();
-        let mut animated_tiles = Vec::new();
-        let mut boss = None;
-
-        let mut enemy_paths = Vec::new();
-        let mut fog_points = Vec::new();
-        let mut camera_offsets = Vec::new();
-
-        let mut forced_player_spawn = None;
-        let mut forced_level_end = None;
-        let mut forced_level_elevator_shaft_height = None;
-
-        let mut horse_arrows = Vec::new();
-
-        for (index, chunks) in layers_chunks.iter().enumerate() {
-            for ((cx, cy), chunk) in chunks.iter() {
-                for (i, tile) in chunk.tiles.iter().enumerate() {
-                    let x = (i % 16) + (*cx - min_x) as usize;
-                    let y = (i / 16) + (*cy - min_y) as usize;
-                    data[x + y * width as usize][index] = *tile;
-                    let pos = vec2(
-                        (x * 8) as f32 + (min_x * 8) as f32,
-                        (y * 8) as f32 + (min_y * 8) as f32,
-                    );
-                    if index == layers_chunks.len() - 1 {
-                        if *tile == 1 {
-                            data[x + y * width as usize][index - 1] = *tile;
-                            lasso_targets.push(pos + vec2(4.0, 4.0));
-                        } else if *tile == 512 + 1 || *tile == 513 + 1 {
-                            data[x + y * width as usize][index - 1] = *tile;
-                        } else if *tile <= 32 && *tile > 1 {
-                            enemies.push(LevelEnemyData {
-                                pos,
-                                ty: &ENEMIES[(*tile - 2) as usize],
-                                attack_delay: 0.0,
-                                path_index: None,
-                                spawner: None,
-                            });
-                        } else if *tile == 384 + 1 {
-                            horses.push(Horse::new(pos, vec2(1.0, 0.0), false));
-                        } else if *tile == 416 + 1 || *tile == 417 + 1 {
-                            horse_arrows.push((pos, *tile == 417 + 1));
-                        } else if *tile > 928 && *tile < 960 + 1 {
-                            boss = Some(((*tile - 1 - 928) as usize, pos));
-                        } else if *tile == 448 + 1 {
-                            forced_player_spawn = Some(pos)
-                        } else if *tile == 449 + 1 {
-                            forced_level_end = Some(pos)
-                        } else if *tile == 450 + 1 {
-                            forced_level_elevator_shaft_height = Some(pos.y);
-                        } else if *tile == 705 + 1 {
-                            camera_offsets.push((pos, 0.0));
-                        }
-                    } else if *tile == 320 + 1 {
-                        animated_tiles.push((pos, 0));
-                    } else if *tile == 256 + 1 {
-                        animated_tiles.push((pos, 1));
-                    }
-                    if *tile == 1024 + 1 {
-                        data[x + y * width as usize][index] = 0;
-                        fog_points.push(pos);
-                    }
-                }
-            }
-        }
-        'horseloop: for horse in horses.iter_mut() {
-            for (arrow, flip) in horse_arrows.iter() {
-                if arrow.distance(horse.pos) == 8.0 {
-                    horse.direction = (*arrow - horse.pos).normalize();
-                    horse.flip = *flip;
-                    continue 'horseloop;
-                }
-            }
-            warn!("no horse arrow found for horse at {:?}", horse.pos);
-        }
-        let mut player_spawn = (usize::MAX, usize::MAX);
-        let mut roof_height = usize::MAX;
-        let mut floor_height = 0;
-        let mut camera = create_camera((width * 8) as f32, (height * 8) as f32);
-        camera.target = vec2((width * 8) as f32 / 2.0, (height * 8) as f32 / 2.0);
-        set_camera(&camera);
-        for (i, tile) in data.iter().enumerate() {
-            let x = i % width as usize;
-            let y = i / width as usize;
-            if tile[1] != 0 {
-                if x < player_spawn.0 {
-                    player_spawn.0 = x;
-                    player_spawn.1 = usize::MAX;
-                }
-                if y < player_spawn.1 && x <= player_spawn.0 {
-                    player_spawn.1 = y;
-                }
-            }
-            if tile[0] + tile[1] + tile[2] != 0 {
-                if y < roof_height {
-                    roof_height = y
-                }
-                if y > floor_height {
-                    floor_height = y;
-                }
-            }
-            for t in &tile[..if DEBUG_FLAGS.special { 4 } else { 3 }] {
-                if *t == 0 {
-                    continue;
-                }
-                let t = *t - 1;
-                tileset.draw_tile(
-                    (x * 8) as f32,
-                    (y * 8) as f32,
-                    (t % 32) as f32,
-                    (t / 32) as f32,
-                    None,
-                );
-            }
-        }
-        let mut visited_tiles = HashSet::new();
-
-        /// For summing number tiles
-        fn sum_number_neighbours(
-            i: usize,
-            width: usize,
-            data: &[[u16; 4]],
-            visited_tiles: &mut HashSet<usize>,
-            sum: &mut u16,
-            factor: &mut f32,
-            number_affectable_tiles: &mut Vec<usize>,
-        ) {
-            let x = i % width;
-            let y = i / width;
-            for d in [(0, 1), (0, -1)] {
-                let nx = x.saturating_add_signed(d.0);
-                let ny = y.saturating_add_signed(d.1);
-                let ni = nx + ny * width;
-                if visited_tiles.contains(&ni) {
-                    continue;
-                }
-                visited_tiles.insert(ni);
-                let tile = data[ni][3];
-                if tile == 0 {
-                    continue;
-                }
-                let tile = tile - 1;
-
-                if (992..=1000).contains(&tile) || (960..=964).contains(&tile) {
-                    if tile < 992 {
-                        *factor *= 0.5_f32.powi((tile - 960 + 1) as i32);
-                    } else {
-                        let value = tile - 992 + 1;
-                        *sum += value;
-                    }
-                    sum_number_neighbours(
-                        ni,
-                        width,
-                        data,
-                        visited_tiles,
-                        sum,
-                        factor,
-                        number_affectable_tiles,
-                    );
-                } else if (tile > 0 && tile < 32) || tile == 705 {
-                    number_affectable_tiles.push(ni);
-                }
-            }
-        }
-        #[expect(clippy::too_many_arguments)]
-        fn follow_path(
-            i: usize,
-            width: usize,
-            data: &[[u16; 4]],
-            min_x: i16,
-            min_y: i16,
-            counter: usize,
-            path_index: usize,
-            visited_tiles: &mut HashSet<usize>,
-            path: &mut Vec<Vec2>,
-            enemies: &mut Vec<LevelEnemyData>,
-        ) {
-            let x = i % width;
-            let y = i / width;
-            // [(0, 1), (-1, 0), (0, -1), (1, 0)]
-            for d in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
-                let nx = x.saturating_add_signed(d.0);
-                let ny = y.saturating_add_signed(d.1);
-                let ni = nx + ny * width;
-                if visited_tiles.contains(&ni) {
-                    continue;
-                }
-                visited_tiles.insert(ni);
-                let tile = data[ni][3];
-                if tile == 0 {
-                    continue;
-                }
-                let pos = vec2(
-                    (nx * 8) as f32 + (min_x * 8) as f32,
-                    (ny * 8) as f32 + (min_y * 8) as f32,
-                );
-                if tile > 1 && tile < 32 {
-                    // find enemy here
-                    let enemy = enemies.iter_mut().find(|f| f.pos == pos).unwrap();
-                    enemy.path_index = Some((path_index, counter));
-                }
-                let tile = tile - 1;
-                if tile == 480 {
-                    path.push(pos);
-                    follow_path(
-                        ni,
-                        width,
-                        data,
-                        min_x,
-                        min_y,
-                        counter + 1,
-                        path_index,
-                        visited_tiles,
-                        path,
-                        enemies,
-                    );
-                }
-            }
-        }
-        for (i, tile) in data.iter().enumerate() {
-            if tile[3] == 0 {
-                continue;
-            }
-            if visited_tiles.contains(&i) {
-                continue;
-            }
-            let tile = tile[3] - 1;
-            // handle spawner
-            if (tile == 481) || (640..=644).contains(&tile) {
-                let x = i % width as usize;
-                let y = i / width as usize;
-
-                let spawner = if tile == 481 {
-                    EnemySpawner::Proximity
-                } else {
-                    EnemySpawner::Trigger((tile - 640) as u8)
-                };
-
-                for d in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
-                    let nx = x.saturating_add_signed(d.0);
-                    let ny = y.saturating_add_signed(d.1);
-                    let ni = nx + ny * width as usize;
-                    let tile = data[ni][3];
-
-                    if tile > 1 && tile < 32 + 1 {
-                        let pos = vec2(
-                            (nx * 8) as f32 + (min_x * 8) as f32,
-                            (ny * 8) as f32 + (min_y * 8) as f32,
-                        );
-
-                        // find enemy here
-                        let enemy = enemies.iter_mut().find(|f| f.pos == pos).unwrap();
-                        enemy.spawner = Some(spawner);
-                    }
-                }
-            }
-
-            // handle enemy paths
-            if tile == 480 {
-                visited_tiles.insert(i);
-                let x = i % width as usize;
-                let y = i / width as usize;
-                let pos = vec2(
-                    (x * 8) as f32 + (min_x * 8) as f32,
-                    (y * 8) as f32 + (min_y * 8) as f32,
-                );
-                let mut path = vec![pos];
-                follow_path(
-                    i,
-                    width as usize,
-                    &data,
-                    min_x,
-                    min_y,
-                    0,
-                    enemy_paths.len(),
-                    &mut visited_tiles,
-                    &mut path,
-                    &mut enemies,
-                );
-                enemy_paths.push(path);
-            }
-
-            // handle number tiles
-            if (992..=1000).contains(&tile) {
-                visited_tiles.insert(i);
-                let mut sum = tile - 992 + 1;
-                let mut factor = 1.0;
-                let mut number_affectable_tiles = Vec::new();
-                sum_number_neighbours(
-                    i,
-                    width as usize,
-                    &data,
-                    &mut visited_tiles,
-                    &mut sum,
-                    &mut factor,
-                    &mut number_affectable_tiles,
-                );
-                let number_y = i / width as usize;
-
-                let sum = sum as f32 * factor;
-                for item in number_affectable_tiles {
-                    let x = item % width as usize;
-                    let y = item / width as usize;
-                    let pos = vec2(
-                        (x * 8) as f32 + (min_x * 8) as f32,
-                        (y * 8) as f32 + (min_y * 8) as f32,
-                    );
-                    let tile_index = data[item][3];
-                    if tile_index > 1 && tile_index < 33 {
-                        let enemy = enemies.iter_mut().find(|f| f.pos == pos).unwrap();
-                        enemy.attack_delay = sum;
-                    } else if tile_index == 705 + 1 {
-                        let value = sum * if number_y > y { -1.0 } else { 1.0 };
-                        // daisy chain all adjacent camera offsets
-                        let offset: isize = if number_y > y { -1 } else { 1 };
-                        let mut y = y;
-                        loop {
-                            if data[x + y * width as usize][3] != 705 + 1 {
-                                break;
-                            }
-                            let pos = vec2(
-                                (x * 8) as f32 + (min_x * 8) as f32,
-                                (y * 8) as f32 + (min_y * 8) as f32,
-                            );
-                            let camera_offset =
-                                camera_offsets.iter_mut().find(|f| f.0 == pos).unwrap();
-                            camera_offset.1 = value;
-                            y = y.saturating_add_signed(offset);
-                        }
-                    }
-                }
-            }
-        }
-        set_default_camera();
-        let min_pos = vec2((min_x * 8) as f32, (min_y * 8) as f32);
-        let player_spawn = vec2(
-            (player_spawn.0 * 8) as f32 + min_pos.x,
-            (player_spawn.1 * 8) as f32 + min_pos.y - 8.0,
+        info!(
+            "loaded {} levels in {} worlds ({})",
+            levels.len(),
+            worlds.len(),
+            worlds
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
-        Self {
-            name,
-            player_spawn,
-            floor_height: (floor_height * 8) as f32 + min_pos.y,
-            roof_height: (roof_height * 8) as f32 + min_pos.y,
-            width: width as usize,
-            max_pos: vec2((max_x * 8) as f32, (max_y * 8) as f32),
-            forced_player_spawn,
-            forced_level_end,
-            forced_level_elevator_shaft_height,
-            fog_points,
-            camera_offsets,
-            enemy_paths,
-            min_pos,
-            boss,
-            lasso_targets,
-            horses,
-            enemies,
-            animated_tiles,
-            camera,
-            data,
-        }
-    }
-}
-#[derive(Clone)]
-pub struct Chunk {
-    pub x: i16,
-    pub y: i16,
-    pub tiles: Vec<u16>,
-}
-
-fn get_all_chunks(xml: &str) -> HashMap<(i16, i16), Chunk> {
-    let mut chunks = HashMap::new();
-    let mut xml = xml.to_string();
-    while let Some((current, remains)) = xml.split_once("</chunk>") {
-        let new = parse_chunk(current);
-        chunks.insert((new.x, new.y), new);
-        xml = remains.to_string();
+        Ok(Self {
+            levels,
+            worlds,
+            portraits: Spritesheet::new(load_ase_texture(&asset!("portraits.ase"), None), 22.0),
+            boss_badges: Spritesheet::new(load_ase_texture(&asset!("boss_badges.ase"), None), 10.0),
+            get_badge: Animation::from_file(&asset!("get_badge.ase")),
+            font: load_ttf_font_from_bytes(&asset!("font.ttf")).unwrap(),
+            clouds: Animation::from_file(&asset!("clouds.ase")),
+            torso: AnimationsGroup::from_file(&asset!("torso.ase")),
+            legs: AnimationsGroup::from_file(&asset!("legs.ase")),
+            elevator: AnimationsGroup::from_file(&asset!("elevator.ase")),
+            doors: AnimationsGroup::from_file(&asset!("doors.ase")),
+            projectiles: AnimationsGroup::from_file(&asset!("projectiles.ase")),
+            horse: AnimationsGroup::from_file(&asset!("horse.ase")),
+            blood: Animation::from_file(&asset!("blood.ase")),
+            die: AnimationsGroup::from_file(&asset!("die.ase")),
+            target: Animation::from_file(&asset!("target.ase")),
+            dialogue: load_ase_texture(&asset!("dialogue.ase"), None),
+            pole: Animation::from_file(&asset!("pole.ase")),
+
+            fireking: AnimationsGroup::from_file(&asset!("fireking.ase")),
+            fireball: AnimationsGroup::from_file(&asset!("fireball.ase")),
+            fire_crown: Animation::from_file(&asset!("fire_crown.ase")),
+            lavafall: Animation::from_file(&asset!("lavafall.ase")),
+            fireking_target: Animation::from_file(&asset!("fireking_target.ase")),
+            fireking_pipe: load_ase_texture(&asset!("fireking_pipe.ase"), None),
+
+            henry: AnimationsGroup::from_file(&asset!("henry.ase")),
+            henry_target: load_ase_texture(&asset!("henry_target.ase"), None),
+            henry_dust: Animation::from_file(&asset!("henry_dust.ase")),
+            tileset,
+        })
     }
-
-    chunks
-}
-
-fn parse_chunk(xml: &str) -> Chunk {
-    let (tag, data) = xml
-        .split_once("<chunk ")
-        .unwrap()
-        .1
-        .split_once(">")
-        .unwrap();
-
-    let x = tag
-        .split_once("x=\"")
-        .unwrap()
-        .1
-        .split_once("\"")
-        .unwrap()
-        .0
-        .parse()
-        .unwrap();
-    let y = tag
-        .split_once("y=\"")
-        .unwrap()
-        .1
-        .split_once("\"")
-        .unwrap()
-        .0
-        .parse()
-        .unwrap();
-
-    let mut split = data.split(',');
-
-    let mut chunk = vec![0; 16 * 16];
-    for item in &mut chunk {
-        let a = split.next().unwrap().trim();
-        *item = a.parse().unwrap()
+    /// Finds a level by its file name, or failing that, the start of its file name
+    pub fn find_level(&self, name: &str) -> Option<usize> {
+        self.levels
+            .iter()
+            .position(|f| f.name == name)
+            .or_else(|| self.levels.iter().position(|f| f.name.starts_with(name)))
     }
-    Chunk { x, y, tiles: chunk }
 }
 
 pub struct Animation {
@@ -706,7 +242,6 @@ impl Animation {
 }
 
 pub struct AnimationsGroup {
-    #[expect(dead_code)]
     pub file: AsepriteFile,
     pub animations: Vec<Animation>,
     pub tag_names: HashMap<String, usize>,
@@ -788,7 +323,6 @@ impl Spritesheet {
             sprite_size,
         }
     }
-    #[expect(dead_code)]
     /// Same as `draw_tile`, except centered
     pub fn draw_sprite(
         &self,
@@ -828,3 +362,19 @@ impl Spritesheet {
         draw_texture_ex(&self.texture, screen_x, screen_y, color, p);
     }
 }
+
+#[test]
+fn reloading_keeps_old_tables_valid() {
+    static TABLE: Reloadable<Vec<u8>> = Reloadable::new("table.toml", || Ok(vec![1]));
+    let old: &'static Vec<u8> = &TABLE;
+    let replaced = TABLE.replace(vec![2]);
+    assert_eq!((old[0], TABLE[0]), (1, 2));
+    TABLE.restore(replaced);
+    assert_eq!(TABLE[0], 1);
+
+    static BROKEN: Reloadable<u8> = Reloadable::new("broken.toml", || Err("oops".to_string()));
+    assert_eq!(
+        BROKEN.read(),
+        Err("couldn't load broken.toml: oops".to_string())
+    );
+}
@@ -0,0 +1,65 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use macroquad::prelude::*;
+
+pub const LEVELS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/levels");
+pub const ASSETS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+/// How often to check the files for changes, in seconds
+const POLL_INTERVAL: f64 = 0.5;
+
+/// What changed since the last poll
+pub enum Change {
+    /// Only files in `levels/` changed
+    Levels,
+    /// Something in `assets/` changed
+    Assets,
+}
+
+/// Watches `levels/` and `assets/` on disk for changes, by polling their files' modification times.
+pub struct HotReloader {
+    last_poll: f64,
+    levels_modified: Option<SystemTime>,
+    assets_modified: Option<SystemTime>,
+}
+impl Default for HotReloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl HotReloader {
+    pub fn new() -> Self {
+        Self {
+            last_poll: get_time(),
+            levels_modified: last_modified(LEVELS_PATH),
+            assets_modified: last_modified(ASSETS_PATH),
+        }
+    }
+    pub fn poll(&mut self) -> Option<Change> {
+        if get_time() - self.last_poll < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = get_time();
+
+        let levels_modified = last_modified(LEVELS_PATH);
+        let assets_modified = last_modified(ASSETS_PATH);
+        let change = if assets_modified != self.assets_modified {
+            Some(Change::Assets)
+        } else if levels_modified != self.levels_modified {
+            Some(Change::Levels)
+        } else {
+            None
+        };
+        self.levels_modified = levels_modified;
+        self.assets_modified = assets_modified;
+        change
+    }
+}
+
+/// The latest modification time of any file in a directory
+fn last_modified(path: &str) -> Option<SystemTime> {
+    fs::read_dir(Path::new(path))
+        .ok()?
+        .filter_map(|f| f.ok()?.metadata().ok()?.modified().ok())
+        .max()
+}
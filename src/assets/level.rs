@@ -0,0 +1,1667 @@
+use std::{
+    cell::{Cell, OnceCell},
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    io::Read,
+    ops::Deref,
+};
+
+use base64::prelude::*;
+use macroquad::prelude::*;
+
+use crate::{
+    assets::{LevelEnd, Spritesheet, TILES, TileKind},
+    bosses::BOSS_MARKERS,
+    enemies::{EnemySpawner, LevelEnemyData, find_enemy_type},
+    utils::{DEBUG_FLAGS, ERASE_MATERIAL, create_camera},
+};
+
+pub use lint::*;
+pub use navigation::*;
+pub use objects::*;
+pub use platforms::*;
+
+mod lint;
+mod navigation;
+mod objects;
+mod platforms;
+
+#[derive(Clone, Copy)]
+pub struct Horse {
+    pub pos: Vec2,
+    pub home_pos: Vec2,
+    pub time: f32,
+    pub velocity: Vec2,
+    pub direction: Vec2,
+    pub flip: bool,
+    pub running: bool,
+    pub player_riding: bool,
+    pub returning_home: bool,
+    /// Set when a spring launches the horse, which falls until it lands again
+    pub falling: bool,
+}
+impl Horse {
+    pub fn is_flipped(&self) -> bool {
+        let mut flip = self.direction.x < 0.0;
+        if self.flip {
+            flip = !flip;
+        }
+        flip
+    }
+    pub fn get_normal(&self) -> Vec2 {
+        Vec2::from_angle(
+            self.direction.to_angle() - PI / 2.0 - if self.is_flipped() { PI } else { 0.0 },
+        )
+    }
+    pub fn new(pos: Vec2, direction: Vec2, flip: bool) -> Self {
+        Self {
+            pos,
+            direction,
+            flip,
+            home_pos: pos,
+            time: 0.0,
+            velocity: Vec2::ZERO,
+            running: false,
+            returning_home: false,
+            falling: false,
+            player_riding: false,
+        }
+    }
+}
+
+/// Everything parsed from a level file. Contains no graphics state, so can be loaded headlessly.
+pub struct LevelData {
+    pub name: String,
+    pub data: Vec<[u16; 4]>,
+    /// How each tile in `data` is flipped/rotated
+    pub flips: Vec<[TileFlip; 4]>,
+    pub width: usize,
+    pub enemies: Vec<LevelEnemyData>,
+    pub horses: Vec<Horse>,
+    pub boss: Option<(usize, Vec2)>,
+    pub min_pos: Vec2,
+    pub max_pos: Vec2,
+    pub player_spawn: Vec2,
+    pub enemy_paths: Vec<Vec<Vec2>>,
+    pub moving_platforms: Vec<MovingPlatform>,
+    // The Y coordinate of the highest point/placed tile (lowest value)
+    pub roof_height: f32,
+    // The Y coordinate of the lowest point/placed tile (highest value)
+    pub floor_height: f32,
+    pub lasso_targets: Vec<Vec2>,
+    /// Positions of tiles with an animation, along with the tile
+    pub animated_tiles: Vec<(Vec2, u16)>,
+
+    pub camera_offsets: Vec<(Vec2, f32)>,
+    /// Areas that set the camera offset while the player is inside them
+    pub camera_zones: Vec<(Rect, f32)>,
+    /// Areas that spawn enemies waiting on the contained trigger ID
+    pub trigger_zones: Vec<(Rect, u8)>,
+    /// Positions of marker tiles (keyed by their index) and named objects
+    pub markers: HashMap<String, Vec2>,
+    /// Problems found while parsing that don't stop the level from loading
+    pub warnings: Vec<LevelWarning>,
+
+    pub fog_points: Vec<Vec2>,
+
+    pub forced_player_spawn: Option<Vec2>,
+    pub forced_level_end: Option<Vec2>,
+    pub forced_level_elevator_shaft_height: Option<f32>,
+}
+impl LevelData {
+    /// Finds a marker by name. Marker tiles are named by their index, i.e. "0" for the first marker tile.
+    pub fn find_marker(&self, name: &str) -> Option<Vec2> {
+        self.markers.get(name).copied()
+    }
+    pub fn get_height(&self) -> f32 {
+        self.floor_height - self.roof_height + 8.0
+    }
+    pub fn get_tile(&self, x: i16, y: i16) -> [u16; 4] {
+        if (x as f32 * 8.0) < self.min_pos.x || ((x - 16) as f32 * 8.0) >= self.max_pos.x {
+            return [0, 1, 0, 0];
+        }
+        if (y as f32 * 8.0) < self.min_pos.y {
+            return [0; 4];
+        }
+        let x = (x - (self.min_pos.x / 8.0) as i16) as usize;
+        let y = (y - (self.min_pos.y / 8.0) as i16) as usize;
+        if x >= self.width || y >= self.data.len() / self.width {
+            return [0; 4];
+        }
+        self.data[x + y * self.width]
+    }
+    /// Whether a point is inside the solid part of a tile on the collision layer
+    pub fn collides_at(&self, pos: Vec2) -> bool {
+        let tile_pos = (pos / 8.0).floor();
+        let tile = self.get_tile(tile_pos.x as i16, tile_pos.y as i16)[1];
+        tile != 0 && TILES.get(tile).collision.contains(pos - tile_pos * 8.0)
+    }
+    pub fn parse(data: &str, name: String) -> Result<Self, LevelLoadError> {
+        let document =
+            roxmltree::Document::parse(data).map_err(|error| LevelLoadError::MalformedXml {
+                level: name.clone(),
+                error,
+            })?;
+        let objects = parse_objects(document.root_element(), &name)?;
+        let layers_chunks = document
+            .root_element()
+            .children()
+            .filter(|f| f.has_tag_name("layer"))
+            .enumerate()
+            .map(|(index, layer)| get_all_chunks(layer, index, &name))
+            .collect::<Result<Vec<HashMap<(i16, i16), Chunk>>, LevelLoadError>>()?;
+        if layers_chunks.len() != LAYER_COUNT {
+            return Err(LevelLoadError::LayerCount {
+                level: name,
+                found: layers_chunks.len(),
+            });
+        }
+        let mut min_x = i16::MAX;
+        let mut max_x = i16::MIN;
+        let mut min_y = i16::MAX;
+        let mut max_y = i16::MIN;
+        for chunk in &layers_chunks {
+            for (x, y) in chunk.keys() {
+                if *x < min_x {
+                    min_x = *x;
+                }
+                if *x > max_x {
+                    max_x = *x;
+                }
+                if *y < min_y {
+                    min_y = *y;
+                }
+                if *y > max_y {
+                    max_y = *y;
+                }
+            }
+        }
+        let width = max_x - min_x + 16;
+        let height = max_y - min_y + 16;
+
+        let mut data = vec![[0; 4]; (width * height) as usize];
+        let mut flips = vec![[TileFlip::default(); 4]; (width * height) as usize];
+        let mut enemies = Vec::new();
+        let mut horses = Vec::new();
+        let mut lasso_targets = Vec::new();
+        let mut animated_tiles = Vec::new();
+        let mut boss = None;
+
+        let mut enemy_paths = Vec::new();
+        let mut fog_points = Vec::new();
+        let mut camera_offsets = Vec::new();
+
+        let mut forced_player_spawn = None;
+        let mut forced_level_end = None;
+        let mut forced_level_elevator_shaft_height = None;
+
+        let mut horse_arrows = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (index, chunks) in layers_chunks.iter().enumerate() {
+            for ((cx, cy), chunk) in chunks.iter() {
+                for (i, (tile, flip)) in chunk.tiles.iter().zip(&chunk.flips).enumerate() {
+                    let x = (i % 16) + (*cx - min_x) as usize;
+                    let y = (i / 16) + (*cy - min_y) as usize;
+                    data[x + y * width as usize][index] = *tile;
+                    flips[x + y * width as usize][index] = *flip;
+                    let pos = vec2(
+                        (x * 8) as f32 + (min_x * 8) as f32,
+                        (y * 8) as f32 + (min_y * 8) as f32,
+                    );
+                    let properties = TILES.get(*tile);
+                    if index == layers_chunks.len() - 1 {
+                        match properties.kind {
+                            TileKind::LassoTarget => {
+                                data[x + y * width as usize][index - 1] = *tile;
+                                flips[x + y * width as usize][index - 1] = *flip;
+                                lasso_targets.push(pos + vec2(4.0, 4.0));
+                            }
+                            TileKind::Climbable => {
+                                data[x + y * width as usize][index - 1] = *tile;
+                                flips[x + y * width as usize][index - 1] = *flip;
+                            }
+                            TileKind::Enemy => {
+                                let Some(ty) =
+                                    properties.enemy.as_deref().and_then(find_enemy_type)
+                                else {
+                                    let (chunk, tile_pos) = chunk_of(pos);
+                                    return Err(LevelLoadError::UnknownEnemy {
+                                        level: name,
+                                        chunk,
+                                        tile: tile_pos,
+                                        id: *tile - 1,
+                                    });
+                                };
+                                enemies.push(LevelEnemyData {
+                                    pos,
+                                    ty,
+                                    attack_delay: 0.0,
+                                    path_index: None,
+                                    spawner: None,
+                                });
+                            }
+                            TileKind::Horse => {
+                                horses.push(Horse::new(pos, vec2(1.0, 0.0), false));
+                            }
+                            TileKind::HorseArrow(flip) => horse_arrows.push((pos, flip)),
+                            TileKind::Boss(index) => boss = Some((index, pos)),
+                            TileKind::PlayerSpawn => forced_player_spawn = Some(pos),
+                            TileKind::LevelEnd => forced_level_end = Some(pos),
+                            TileKind::ElevatorShaft => {
+                                forced_level_elevator_shaft_height = Some(pos.y)
+                            }
+                            TileKind::CameraOffset => camera_offsets.push((pos, 0.0)),
+                            _ => {}
+                        }
+                    } else if properties.animation.is_some() {
+                        animated_tiles.push((pos, *tile));
+                    }
+                    if properties.kind == TileKind::Fog {
+                        data[x + y * width as usize][index] = 0;
+                        flips[x + y * width as usize][index] = TileFlip::default();
+                        fog_points.push(pos);
+                    }
+                }
+            }
+        }
+        'horseloop: for horse in horses.iter_mut() {
+            for (arrow, flip) in horse_arrows.iter() {
+                if arrow.distance(horse.pos) == 8.0 {
+                    horse.direction = (*arrow - horse.pos).normalize();
+                    horse.flip = *flip;
+                    continue 'horseloop;
+                }
+            }
+            warnings.push(LevelWarning::new(
+                horse.pos,
+                "horse has no arrow next to it".to_string(),
+            ));
+        }
+        let mut player_spawn = (usize::MAX, usize::MAX);
+        let mut roof_height = usize::MAX;
+        let mut floor_height = 0;
+        let mut markers = HashMap::new();
+        for (i, tile) in data.iter().enumerate() {
+            let x = i % width as usize;
+            let y = i / width as usize;
+            if let TileKind::Marker(marker) = TILES.get(tile[3]).kind {
+                let pos = vec2(
+                    (x * 8) as f32 + (min_x * 8) as f32,
+                    (y * 8) as f32 + (min_y * 8) as f32,
+                );
+                markers.entry(marker.to_string()).or_insert(pos);
+            }
+            if tile[1] != 0 {
+                if x < player_spawn.0 {
+                    player_spawn.0 = x;
+                    player_spawn.1 = usize::MAX;
+                }
+                if y < player_spawn.1 && x <= player_spawn.0 {
+                    player_spawn.1 = y;
+                }
+            }
+            if tile[0] + tile[1] + tile[2] != 0 {
+                if y < roof_height {
+                    roof_height = y
+                }
+                if y > floor_height {
+                    floor_height = y;
+                }
+            }
+        }
+        let mut visited_tiles = HashSet::new();
+
+        /// For summing number tiles
+        fn sum_number_neighbours(
+            i: usize,
+            width: usize,
+            data: &[[u16; 4]],
+            visited_tiles: &mut HashSet<usize>,
+            sum: &mut u16,
+            factor: &mut f32,
+            number_affectable_tiles: &mut Vec<usize>,
+        ) {
+            let x = i % width;
+            let y = i / width;
+            for d in [(0, 1), (0, -1)] {
+                let nx = x.saturating_add_signed(d.0);
+                let ny = y.saturating_add_signed(d.1);
+                let ni = nx + ny * width;
+                if visited_tiles.contains(&ni) {
+                    continue;
+                }
+                visited_tiles.insert(ni);
+                let Some(tile) = data.get(ni).map(|f| f[3]) else {
+                    continue;
+                };
+                let kind = TILES.get(tile).kind;
+                if let TileKind::Number(_) | TileKind::Half(_) = kind {
+                    match kind {
+                        TileKind::Half(halvings) => *factor *= 0.5_f32.powi(halvings),
+                        TileKind::Number(value) => *sum += value,
+                        _ => {}
+                    }
+                    sum_number_neighbours(
+                        ni,
+                        width,
+                        data,
+                        visited_tiles,
+                        sum,
+                        factor,
+                        number_affectable_tiles,
+                    );
+                } else if let TileKind::Enemy | TileKind::CameraOffset = kind {
+                    number_affectable_tiles.push(ni);
+                }
+            }
+        }
+        #[expect(clippy::too_many_arguments)]
+        fn follow_path(
+            i: usize,
+            width: usize,
+            data: &[[u16; 4]],
+            min_x: i16,
+            min_y: i16,
+            counter: usize,
+            path_index: usize,
+            visited_tiles: &mut HashSet<usize>,
+            path: &mut Vec<Vec2>,
+            enemies: &mut Vec<LevelEnemyData>,
+        ) -> Result<(), Vec2> {
+            let x = i % width;
+            let y = i / width;
+            // [(0, 1), (-1, 0), (0, -1), (1, 0)]
+            for d in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+                let nx = x.saturating_add_signed(d.0);
+                let ny = y.saturating_add_signed(d.1);
+                let ni = nx + ny * width;
+                if visited_tiles.contains(&ni) {
+                    continue;
+                }
+                visited_tiles.insert(ni);
+                let Some(tile) = data.get(ni).map(|f| f[3]) else {
+                    continue;
+                };
+                let pos = vec2(
+                    (nx * 8) as f32 + (min_x * 8) as f32,
+                    (ny * 8) as f32 + (min_y * 8) as f32,
+                );
+                let kind = TILES.get(tile).kind;
+                if let TileKind::Enemy = kind {
+                    // find enemy here
+                    let enemy = enemies.iter_mut().find(|f| f.pos == pos).ok_or(pos)?;
+                    enemy.path_index = Some((path_index, counter));
+                }
+                if kind == TileKind::EnemyPath {
+                    path.push(pos);
+                    follow_path(
+                        ni,
+                        width,
+                        data,
+                        min_x,
+                        min_y,
+                        counter + 1,
+                        path_index,
+                        visited_tiles,
+                        path,
+                        enemies,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+        for (i, tile) in data.iter().enumerate() {
+            if tile[3] == 0 {
+                continue;
+            }
+            if visited_tiles.contains(&i) {
+                continue;
+            }
+            let kind = TILES.get(tile[3]).kind;
+            // handle spawner
+            let spawner = match kind {
+                TileKind::Spawner => Some(EnemySpawner::Proximity),
+                TileKind::TriggerSpawner(id) => Some(EnemySpawner::Trigger(id)),
+                _ => None,
+            };
+            if let Some(spawner) = spawner {
+                let x = i % width as usize;
+                let y = i / width as usize;
+
+                for d in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+                    let (Some(nx), Some(ny)) =
+                        (x.checked_add_signed(d.0), y.checked_add_signed(d.1))
+                    else {
+                        continue;
+                    };
+                    if nx >= width as usize {
+                        continue;
+                    }
+                    let ni = nx + ny * width as usize;
+                    let Some(tile) = data.get(ni).map(|f| f[3]) else {
+                        continue;
+                    };
+
+                    if let TileKind::Enemy = TILES.get(tile).kind {
+                        let pos = vec2(
+                            (nx * 8) as f32 + (min_x * 8) as f32,
+                            (ny * 8) as f32 + (min_y * 8) as f32,
+                        );
+
+                        // find enemy here
+                        let Some(enemy) = enemies.iter_mut().find(|f| f.pos == pos) else {
+                            let (chunk, tile) = chunk_of(pos);
+                            return Err(LevelLoadError::MissingEnemy {
+                                level: name,
+                                chunk,
+                                tile,
+                                attached_by: "spawner",
+                            });
+                        };
+                        enemy.spawner = Some(spawner);
+                    }
+                }
+            }
+
+            // handle enemy paths
+            if kind == TileKind::EnemyPath {
+                visited_tiles.insert(i);
+                let x = i % width as usize;
+                let y = i / width as usize;
+                let pos = vec2(
+                    (x * 8) as f32 + (min_x * 8) as f32,
+                    (y * 8) as f32 + (min_y * 8) as f32,
+                );
+                let mut path = vec![pos];
+                if let Err(pos) = follow_path(
+                    i,
+                    width as usize,
+                    &data,
+                    min_x,
+                    min_y,
+                    0,
+                    enemy_paths.len(),
+                    &mut visited_tiles,
+                    &mut path,
+                    &mut enemies,
+                ) {
+                    let (chunk, tile) = chunk_of(pos);
+                    return Err(LevelLoadError::MissingEnemy {
+                        level: name,
+                        chunk,
+                        tile,
+                        attached_by: "enemy path",
+                    });
+                }
+                enemy_paths.push(path);
+            }
+
+            // handle number tiles
+            if let TileKind::Number(value) = kind {
+                visited_tiles.insert(i);
+                let mut sum = value;
+                let mut factor = 1.0;
+                let mut number_affectable_tiles = Vec::new();
+                sum_number_neighbours(
+                    i,
+                    width as usize,
+                    &data,
+                    &mut visited_tiles,
+                    &mut sum,
+                    &mut factor,
+                    &mut number_affectable_tiles,
+                );
+                let number_y = i / width as usize;
+                if number_affectable_tiles.is_empty() {
+                    let pos = vec2(
+                        ((i % width as usize) * 8) as f32 + (min_x * 8) as f32,
+                        (number_y * 8) as f32 + (min_y * 8) as f32,
+                    );
+                    warnings.push(LevelWarning::new(
+                        pos,
+                        "number tile isn't attached to an enemy or camera offset".to_string(),
+                    ));
+                }
+
+                let sum = sum as f32 * factor;
+                for item in number_affectable_tiles {
+                    let x = item % width as usize;
+                    let y = item / width as usize;
+                    let pos = vec2(
+                        (x * 8) as f32 + (min_x * 8) as f32,
+                        (y * 8) as f32 + (min_y * 8) as f32,
+                    );
+                    let kind = TILES.get(data[item][3]).kind;
+                    if let TileKind::Enemy = kind {
+                        let Some(enemy) = enemies.iter_mut().find(|f| f.pos == pos) else {
+                            let (chunk, tile) = chunk_of(pos);
+                            return Err(LevelLoadError::MissingEnemy {
+                                level: name,
+                                chunk,
+                                tile,
+                                attached_by: "number tile",
+                            });
+                        };
+                        enemy.attack_delay = sum;
+                    } else if kind == TileKind::CameraOffset {
+                        let value = sum * if number_y > y { -1.0 } else { 1.0 };
+                        // daisy chain all adjacent camera offsets
+                        let offset: isize = if number_y > y { -1 } else { 1 };
+                        let mut y = y;
+                        loop {
+                            if data
+                                .get(x + y * width as usize)
+                                .is_none_or(|f| TILES.get(f[3]).kind != TileKind::CameraOffset)
+                            {
+                                break;
+                            }
+                            let pos = vec2(
+                                (x * 8) as f32 + (min_x * 8) as f32,
+                                (y * 8) as f32 + (min_y * 8) as f32,
+                            );
+                            let Some(camera_offset) =
+                                camera_offsets.iter_mut().find(|f| f.0 == pos)
+                            else {
+                                let (chunk, tile) = chunk_of(pos);
+                                return Err(LevelLoadError::MissingCameraOffset {
+                                    level: name,
+                                    chunk,
+                                    tile,
+                                });
+                            };
+                            camera_offset.1 = value;
+                            y = y.saturating_add_signed(offset);
+                        }
+                    }
+                }
+            }
+        }
+        if player_spawn.0 == usize::MAX {
+            return Err(LevelLoadError::EmptyCollisionLayer { level: name });
+        }
+        let min_pos = vec2((min_x * 8) as f32, (min_y * 8) as f32);
+        let player_spawn = vec2(
+            (player_spawn.0 * 8) as f32 + min_pos.x,
+            (player_spawn.1 * 8) as f32 + min_pos.y - 8.0,
+        );
+        let mut level = Self {
+            name,
+            player_spawn,
+            floor_height: (floor_height * 8) as f32 + min_pos.y,
+            roof_height: (roof_height * 8) as f32 + min_pos.y,
+            width: width as usize,
+            max_pos: vec2((max_x * 8) as f32, (max_y * 8) as f32),
+            forced_player_spawn,
+            forced_level_end,
+            forced_level_elevator_shaft_height,
+            fog_points,
+            camera_offsets,
+            enemy_paths,
+            moving_platforms: Vec::new(),
+            min_pos,
+            boss,
+            lasso_targets,
+            horses,
+            enemies,
+            animated_tiles,
+            data,
+            flips,
+            camera_zones: Vec::new(),
+            trigger_zones: Vec::new(),
+            markers,
+            warnings,
+        };
+        level.find_moving_platforms();
+        level.apply_objects(&objects)?;
+        level.check_boss()?;
+        Ok(level)
+    }
+    /// Makes the level's boss tile spawn the boss called `name`, instead of the boss given by the tile.
+    pub fn set_boss(&mut self, name: &str) -> Result<(), LevelLoadError> {
+        let Some(index) = BOSS_MARKERS.iter().position(|f| f.0 == name) else {
+            return Err(LevelLoadError::UnknownBossName {
+                level: self.name.clone(),
+                boss: name.to_string(),
+            });
+        };
+        let Some(boss) = &mut self.boss else {
+            return Err(LevelLoadError::MissingBossTile {
+                level: self.name.clone(),
+            });
+        };
+        boss.0 = index;
+        self.check_boss()
+    }
+    /// Checks that the level's boss exists and has every marker it needs
+    fn check_boss(&self) -> Result<(), LevelLoadError> {
+        let Some((index, pos)) = self.boss else {
+            return Ok(());
+        };
+        let Some((boss_name, required_markers)) = BOSS_MARKERS.get(index) else {
+            let (chunk, tile) = chunk_of(pos);
+            return Err(LevelLoadError::UnknownBoss {
+                level: self.name.clone(),
+                chunk,
+                tile,
+                id: index,
+            });
+        };
+        if let Some(marker) = required_markers
+            .iter()
+            .find(|f| !self.markers.contains_key(**f))
+        {
+            return Err(LevelLoadError::MissingMarker {
+                level: self.name.clone(),
+                marker,
+                required_by: boss_name,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A parsed level, along with its render target which is only baked once it is first drawn.
+pub struct Level {
+    level_data: LevelData,
+    camera: OnceCell<Camera2D>,
+    /// Built the first time an enemy needs it, and again after tiles are destroyed
+    navigation: OnceCell<Navigation>,
+    /// Seconds since the level was started, which moving platforms are positioned by
+    time: Cell<f32>,
+    /// Collision layer tiles destroyed since the level was started, along with their index in `data`
+    broken: Vec<(usize, u16)>,
+    /// Index of the world the level is in
+    pub world: usize,
+    pub end: LevelEnd,
+}
+impl Deref for Level {
+    type Target = LevelData;
+
+    fn deref(&self) -> &LevelData {
+        &self.level_data
+    }
+}
+impl Level {
+    pub fn new(level_data: LevelData) -> Self {
+        Self {
+            level_data,
+            camera: OnceCell::new(),
+            navigation: OnceCell::new(),
+            time: Cell::new(0.0),
+            broken: Vec::new(),
+            world: 0,
+            end: LevelEnd::default(),
+        }
+    }
+    /// Seconds since the level was started
+    pub fn time(&self) -> f32 {
+        self.time.get()
+    }
+    /// Moves the level's moving platforms forward by `delta_time`
+    pub fn advance_time(&self, delta_time: f32) {
+        self.time.set(self.time.get() + delta_time);
+    }
+    /// Moves the level's moving platforms back to where they start, and puts back destroyed tiles
+    pub fn restart(&mut self) {
+        self.time.set(0.0);
+        if self.broken.is_empty() {
+            return;
+        }
+        for (index, tile) in self.broken.drain(..) {
+            self.level_data.data[index][1] = tile;
+        }
+        // rebaked when next drawn
+        self.camera = OnceCell::new();
+        self.navigation = OnceCell::new();
+    }
+    /// Where enemies walking through the level can go
+    pub fn navigation(&self) -> &Navigation {
+        self.navigation.get_or_init(|| Navigation::new(self))
+    }
+    /// Removes the breakable tiles whose centre is within `radius` of `pos` from the collision layer.
+    /// Returns the position and tile of each one.
+    pub fn break_tiles(&mut self, pos: Vec2, radius: f32) -> Vec<(Vec2, u16)> {
+        let height = self.data.len() / self.width;
+        let start = ((pos - radius - self.min_pos) / 8.0)
+            .floor()
+            .max(Vec2::ZERO);
+        let end = ((pos + radius - self.min_pos) / 8.0)
+            .ceil()
+            .min(vec2(self.width as f32, height as f32));
+        let mut broken = Vec::new();
+        for y in start.y as usize..end.y as usize {
+            for x in start.x as usize..end.x as usize {
+                let index = x + y * self.width;
+                let tile = self.data[index][1];
+                let tile_pos = vec2((x * 8) as f32, (y * 8) as f32) + self.min_pos;
+                if !TILES.get(tile).breakable || (tile_pos + 4.0).distance(pos) > radius {
+                    continue;
+                }
+                self.level_data.data[index][1] = 0;
+                self.broken.push((index, tile));
+                broken.push((tile_pos, tile));
+            }
+        }
+        if !broken.is_empty() {
+            self.navigation = OnceCell::new();
+        }
+        broken
+    }
+    /// Breaks tiles like [Level::break_tiles], and patches them out of the level's texture if it has been baked.
+    pub fn explode(&mut self, pos: Vec2, radius: f32, tileset: &Spritesheet) -> Vec<(Vec2, u16)> {
+        let broken = self.break_tiles(pos, radius);
+        let Some(camera) = self.camera.get() else {
+            return broken;
+        };
+        push_camera_state();
+        set_camera(camera);
+        for (tile_pos, _) in broken.iter() {
+            let local = *tile_pos - self.min_pos;
+            // blending is off, so this clears the tile instead of drawing nothing
+            gl_use_material(&ERASE_MATERIAL);
+            draw_rectangle(local.x, local.y, 8.0, 8.0, BLANK);
+            gl_use_default_material();
+            let index = (local.x / 8.0) as usize + (local.y / 8.0) as usize * self.width;
+            self.draw_tile_layers(index, tileset, DEBUG_FLAGS.special);
+        }
+        pop_camera_state();
+        broken
+    }
+    /// Returns the level's texture, baking it first if it hasn't been drawn before.
+    pub fn texture(&self, tileset: &Spritesheet) -> &Texture2D {
+        let camera = self
+            .camera
+            .get_or_init(|| self.render(tileset, DEBUG_FLAGS.special, 1));
+        &camera.render_target.as_ref().unwrap().texture
+    }
+    /// Draws the level's tiles into a new render target, at `scale` pixels per pixel.
+    /// The special layer is only drawn if `special` is set.
+    pub fn render(&self, tileset: &Spritesheet, special: bool, scale: u32) -> Camera2D {
+        let width = (self.width * 8) as f32;
+        let height = (self.data.len() / self.width * 8) as f32;
+        let mut camera = create_camera(width * scale as f32, height * scale as f32);
+        camera.zoom = vec2(2.0 / width, 2.0 / height);
+        camera.target = vec2(width / 2.0, height / 2.0);
+
+        push_camera_state();
+        set_camera(&camera);
+        clear_background(BLANK);
+        for i in 0..self.data.len() {
+            self.draw_tile_layers(i, tileset, special);
+        }
+        pop_camera_state();
+        camera
+    }
+    /// Draws every layer of the tile at `index` in `data`, relative to the level's top left corner
+    fn draw_tile_layers(&self, index: usize, tileset: &Spritesheet, special: bool) {
+        let x = index % self.width;
+        let y = index / self.width;
+        let layers = if special { 4 } else { 3 };
+        for (t, flip) in self.data[index][..layers].iter().zip(&self.flips[index]) {
+            if *t == 0 {
+                continue;
+            }
+            let t = *t - 1;
+            tileset.draw_tile(
+                (x * 8) as f32,
+                (y * 8) as f32,
+                (t % 32) as f32,
+                (t / 32) as f32,
+                Some((flip.draw_params(), WHITE)),
+            );
+        }
+    }
+}
+#[derive(Clone)]
+pub struct Chunk {
+    pub x: i16,
+    pub y: i16,
+    pub tiles: Vec<u16>,
+    pub flips: Vec<TileFlip>,
+}
+
+/// How a tile is mirrored, as stored by Tiled in the top bits of its GID.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct TileFlip {
+    pub horizontal: bool,
+    pub vertical: bool,
+    /// Flipped across the top-left to bottom-right diagonal, i.e. rotated, when combined with the other flips
+    pub diagonal: bool,
+}
+impl TileFlip {
+    const HORIZONTAL_BIT: u32 = 0x80000000;
+    const VERTICAL_BIT: u32 = 0x40000000;
+    const DIAGONAL_BIT: u32 = 0x20000000;
+    /// Only used by hexagonal maps, but stripped so it can't corrupt the tile id
+    const HEXAGONAL_BIT: u32 = 0x10000000;
+
+    /// Splits a GID into its tile id (without flags) and flip flags
+    pub fn from_gid(gid: u32) -> (u32, Self) {
+        let flip = Self {
+            horizontal: gid & Self::HORIZONTAL_BIT != 0,
+            vertical: gid & Self::VERTICAL_BIT != 0,
+            diagonal: gid & Self::DIAGONAL_BIT != 0,
+        };
+        let flags =
+            Self::HORIZONTAL_BIT | Self::VERTICAL_BIT | Self::DIAGONAL_BIT | Self::HEXAGONAL_BIT;
+        (gid & !flags, flip)
+    }
+    /// Params for drawing a tile with this flip.
+    ///
+    /// Tiled applies the diagonal flip first, which equals flipping vertically and then rotating a quarter turn clockwise.
+    /// Moving the horizontal/vertical flips to before the rotation swaps them.
+    pub fn draw_params(&self) -> DrawTextureParams {
+        if self.diagonal {
+            DrawTextureParams {
+                rotation: PI / 2.0,
+                flip_x: self.vertical,
+                flip_y: !self.horizontal,
+                ..Default::default()
+            }
+        } else {
+            DrawTextureParams {
+                flip_x: self.horizontal,
+                flip_y: self.vertical,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// The amount of tile layers every level is expected to have (background, collision, details and special)
+const LAYER_COUNT: usize = 4;
+
+#[derive(Debug)]
+pub enum LevelLoadError {
+    /// The file isn't valid XML. The contained error holds the line and column.
+    MalformedXml {
+        level: String,
+        error: roxmltree::Error,
+    },
+    /// The level doesn't have exactly [LAYER_COUNT] tile layers
+    LayerCount { level: String, found: usize },
+    /// A layer has no `<data>`, or uses an encoding that isn't supported
+    UnsupportedLayer {
+        level: String,
+        layer: usize,
+        reason: String,
+    },
+    /// The collision layer has no tiles, so there is nowhere to place the player
+    EmptyCollisionLayer { level: String },
+    /// A chunk attribute is missing or isn't a valid number
+    BadChunkAttribute {
+        level: String,
+        layer: usize,
+        attribute: &'static str,
+        value: Option<String>,
+    },
+    /// A chunk's encoded data couldn't be decoded or decompressed
+    BadChunkData {
+        level: String,
+        layer: usize,
+        chunk: (i16, i16),
+        reason: String,
+    },
+    /// A chunk doesn't contain exactly 16x16 tiles
+    BadChunkSize {
+        level: String,
+        layer: usize,
+        chunk: (i16, i16),
+        found: usize,
+    },
+    /// A tile value in a chunk isn't a valid tile id
+    BadTileValue {
+        level: String,
+        layer: usize,
+        chunk: (i16, i16),
+        index: usize,
+        value: String,
+    },
+    /// An enemy tile doesn't name any enemy type in `enemies.toml`
+    UnknownEnemy {
+        level: String,
+        chunk: (i16, i16),
+        tile: (i16, i16),
+        id: u16,
+    },
+    /// A tile that attaches to an enemy (path, spawner or number tile) found no enemy where one was expected
+    MissingEnemy {
+        level: String,
+        chunk: (i16, i16),
+        tile: (i16, i16),
+        attached_by: &'static str,
+    },
+    /// A number tile is attached to a camera offset tile that wasn't registered
+    MissingCameraOffset {
+        level: String,
+        chunk: (i16, i16),
+        tile: (i16, i16),
+    },
+    /// A boss tile doesn't correspond to any boss
+    UnknownBoss {
+        level: String,
+        chunk: (i16, i16),
+        tile: (i16, i16),
+        id: usize,
+    },
+    /// A marker needed by the level's boss is missing
+    MissingMarker {
+        level: String,
+        marker: &'static str,
+        required_by: &'static str,
+    },
+    /// A file listed by the manifest doesn't exist
+    MissingFile { level: String },
+    BadManifest {
+        level: String,
+        error: toml::de::Error,
+    },
+    /// The manifest doesn't list any levels
+    NoLevels { level: String },
+    /// The manifest assigns a boss that doesn't exist
+    UnknownBossName { level: String, boss: String },
+    /// The manifest assigns a boss to a level without a boss tile
+    MissingBossTile { level: String },
+    /// An object in an object layer is malformed, or is missing what its class requires
+    BadObject {
+        level: String,
+        object: u32,
+        reason: String,
+    },
+}
+impl LevelLoadError {
+    pub fn level(&self) -> &str {
+        match self {
+            Self::MalformedXml { level, .. }
+            | Self::LayerCount { level, .. }
+            | Self::UnsupportedLayer { level, .. }
+            | Self::EmptyCollisionLayer { level }
+            | Self::BadChunkAttribute { level, .. }
+            | Self::BadChunkData { level, .. }
+            | Self::BadChunkSize { level, .. }
+            | Self::BadTileValue { level, .. }
+            | Self::UnknownEnemy { level, .. }
+            | Self::MissingEnemy { level, .. }
+            | Self::MissingCameraOffset { level, .. }
+            | Self::UnknownBoss { level, .. }
+            | Self::MissingMarker { level, .. }
+            | Self::MissingFile { level }
+            | Self::BadManifest { level, .. }
+            | Self::NoLevels { level }
+            | Self::UnknownBossName { level, .. }
+            | Self::MissingBossTile { level }
+            | Self::BadObject { level, .. } => level,
+        }
+    }
+}
+impl std::fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.level())?;
+        match self {
+            Self::MalformedXml { error, .. } => write!(f, "malformed xml: {error}"),
+            Self::LayerCount { found, .. } => {
+                write!(f, "expected {LAYER_COUNT} tile layers, found {found}")
+            }
+            Self::UnsupportedLayer { layer, reason, .. } => write!(f, "layer {layer}: {reason}"),
+            Self::EmptyCollisionLayer { .. } => write!(
+                f,
+                "collision layer is empty, so there's nowhere to place the player spawn"
+            ),
+            Self::BadChunkAttribute {
+                layer,
+                attribute,
+                value,
+                ..
+            } => match value {
+                Some(value) => write!(
+                    f,
+                    "layer {layer}: chunk has invalid {attribute:?} attribute {value:?}"
+                ),
+                None => write!(f, "layer {layer}: chunk is missing {attribute:?} attribute"),
+            },
+            Self::BadChunkData {
+                layer,
+                chunk,
+                reason,
+                ..
+            } => write!(f, "layer {layer}, chunk {chunk:?}: {reason}"),
+            Self::BadChunkSize {
+                layer,
+                chunk,
+                found,
+                ..
+            } => write!(
+                f,
+                "layer {layer}, chunk {chunk:?}: expected {} tiles, found {found}",
+                16 * 16
+            ),
+            Self::BadTileValue {
+                layer,
+                chunk,
+                index,
+                value,
+                ..
+            } => write!(
+                f,
+                "layer {layer}, chunk {chunk:?}: tile {index} has invalid value {value:?}"
+            ),
+            Self::UnknownEnemy {
+                chunk, tile, id, ..
+            } => {
+                write!(
+                    f,
+                    "chunk {chunk:?}, tile {tile:?}: no enemy type for tile id {id}"
+                )
+            }
+            Self::MissingEnemy {
+                chunk,
+                tile,
+                attached_by,
+                ..
+            } => write!(
+                f,
+                "chunk {chunk:?}, tile {tile:?}: {attached_by} is attached to an enemy that doesn't exist"
+            ),
+            Self::MissingCameraOffset { chunk, tile, .. } => write!(
+                f,
+                "chunk {chunk:?}, tile {tile:?}: number tile is attached to a missing camera offset"
+            ),
+            Self::UnknownBoss {
+                chunk, tile, id, ..
+            } => write!(
+                f,
+                "chunk {chunk:?}, tile {tile:?}: boss tile doesn't match any boss (id {id})"
+            ),
+            Self::MissingMarker {
+                marker,
+                required_by,
+                ..
+            } => write!(
+                f,
+                "{required_by} needs marker {marker:?}, but the level has none"
+            ),
+            Self::MissingFile { .. } => write!(f, "file not found"),
+            Self::BadManifest { error, .. } => write!(f, "malformed manifest: {error}"),
+            Self::NoLevels { .. } => write!(f, "the manifest doesn't list any levels"),
+            Self::UnknownBossName { boss, .. } => write!(f, "there is no boss called {boss:?}"),
+            Self::MissingBossTile { .. } => {
+                write!(f, "a boss is assigned, but the level has no boss tile")
+            }
+            Self::BadObject { object, reason, .. } => write!(f, "object {object}: {reason}"),
+        }
+    }
+}
+impl std::error::Error for LevelLoadError {}
+
+/// Returns the coordinates of the chunk (in tiles) that contains the tile at world position `pos`
+fn chunk_of(pos: Vec2) -> ((i16, i16), (i16, i16)) {
+    let tile = ((pos.x / 8.0).floor() as i16, (pos.y / 8.0).floor() as i16);
+    (
+        (tile.0.div_euclid(16) * 16, tile.1.div_euclid(16) * 16),
+        tile,
+    )
+}
+
+fn get_all_chunks(
+    layer: roxmltree::Node,
+    layer_index: usize,
+    level: &str,
+) -> Result<HashMap<(i16, i16), Chunk>, LevelLoadError> {
+    let unsupported = |reason: String| LevelLoadError::UnsupportedLayer {
+        level: level.to_string(),
+        layer: layer_index,
+        reason,
+    };
+    let data = layer
+        .children()
+        .find(|f| f.has_tag_name("data"))
+        .ok_or_else(|| unsupported("layer has no <data>".to_string()))?;
+    let compression = match data.attribute("compression") {
+        None => None,
+        Some("zlib") => Some(Compression::Zlib),
+        Some("gzip") => Some(Compression::Gzip),
+        Some("zstd") => Some(Compression::Zstd),
+        Some(other) => return Err(unsupported(format!("unsupported compression {other:?}"))),
+    };
+    let encoding = match (data.attribute("encoding"), compression) {
+        (None, None) => LayerEncoding::Xml,
+        (Some("csv"), None) => LayerEncoding::Csv,
+        (Some("base64"), compression) => LayerEncoding::Base64(compression),
+        (encoding, _) => {
+            return Err(unsupported(format!(
+                "unsupported encoding {:?} with compression {:?}",
+                encoding.unwrap_or("xml"),
+                data.attribute("compression")
+            )));
+        }
+    };
+
+    let mut chunks = HashMap::new();
+    for chunk in data.children().filter(|f| f.has_tag_name("chunk")) {
+        let new = parse_chunk(chunk, encoding, layer_index, level)?;
+        chunks.insert((new.x, new.y), new);
+    }
+
+    Ok(chunks)
+}
+
+/// How the tiles of a layer are stored. Tiled can write any of these for infinite maps.
+#[derive(Clone, Copy)]
+enum LayerEncoding {
+    /// One `<tile gid="..."/>` element per tile. Deprecated by Tiled, but still readable.
+    Xml,
+    Csv,
+    /// Little-endian `u32` tile ids, optionally compressed
+    Base64(Option<Compression>),
+}
+
+#[derive(Clone, Copy)]
+enum Compression {
+    Zlib,
+    Gzip,
+    Zstd,
+}
+
+fn decompress(bytes: &[u8], compression: Compression) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    match compression {
+        Compression::Zlib => flate2::read::ZlibDecoder::new(bytes).read_to_end(&mut decompressed),
+        Compression::Gzip => flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed),
+        Compression::Zstd => ruzstd::decoding::StreamingDecoder::new(bytes)
+            .map_err(std::io::Error::other)?
+            .read_to_end(&mut decompressed),
+    }?;
+    Ok(decompressed)
+}
+
+/// Reads the raw tile values of a chunk, as strings so they can be reported if invalid.
+fn read_chunk_values(
+    chunk: roxmltree::Node,
+    encoding: LayerEncoding,
+) -> Result<Vec<String>, String> {
+    match encoding {
+        LayerEncoding::Xml => Ok(chunk
+            .children()
+            .filter(|f| f.has_tag_name("tile"))
+            .map(|f| f.attribute("gid").unwrap_or("0").to_string())
+            .collect()),
+        LayerEncoding::Csv => Ok(chunk
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(|f| f.trim().to_string())
+            .collect()),
+        LayerEncoding::Base64(compression) => {
+            let text: String = chunk
+                .text()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect();
+            let bytes = BASE64_STANDARD
+                .decode(text)
+                .map_err(|f| format!("invalid base64: {f}"))?;
+            let bytes = match compression {
+                Some(compression) => decompress(&bytes, compression)
+                    .map_err(|f| format!("failed to decompress: {f}"))?,
+                None => bytes,
+            };
+            if !bytes.len().is_multiple_of(4) {
+                return Err(format!(
+                    "{} bytes isn't a whole amount of tiles",
+                    bytes.len()
+                ));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|f| u32::from_le_bytes([f[0], f[1], f[2], f[3]]).to_string())
+                .collect())
+        }
+    }
+}
+
+fn parse_chunk(
+    chunk: roxmltree::Node,
+    encoding: LayerEncoding,
+    layer_index: usize,
+    level: &str,
+) -> Result<Chunk, LevelLoadError> {
+    let attribute = |attribute: &'static str| -> Result<i16, LevelLoadError> {
+        let value = chunk.attribute(attribute);
+        value
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| LevelLoadError::BadChunkAttribute {
+                level: level.to_string(),
+                layer: layer_index,
+                attribute,
+                value: value.map(str::to_string),
+            })
+    };
+    let x = attribute("x")?;
+    let y = attribute("y")?;
+
+    let values =
+        read_chunk_values(chunk, encoding).map_err(|reason| LevelLoadError::BadChunkData {
+            level: level.to_string(),
+            layer: layer_index,
+            chunk: (x, y),
+            reason,
+        })?;
+    if values.len() != 16 * 16 {
+        return Err(LevelLoadError::BadChunkSize {
+            level: level.to_string(),
+            layer: layer_index,
+            chunk: (x, y),
+            found: values.len(),
+        });
+    }
+
+    let mut tiles = vec![0; 16 * 16];
+    let mut flips = vec![TileFlip::default(); 16 * 16];
+    for (index, value) in values.into_iter().enumerate() {
+        let parsed = value
+            .parse()
+            .ok()
+            .map(TileFlip::from_gid)
+            .and_then(|(tile, flip)| Some((u16::try_from(tile).ok()?, flip)));
+        let Some((tile, flip)) = parsed else {
+            return Err(LevelLoadError::BadTileValue {
+                level: level.to_string(),
+                layer: layer_index,
+                chunk: (x, y),
+                index,
+                value,
+            });
+        };
+        tiles[index] = tile;
+        flips[index] = flip;
+    }
+    Ok(Chunk { x, y, tiles, flips })
+}
+
+/// Builds a level with a floor, where the special layer consists of `special_chunk`, encoded as described by `data_attributes`.
+#[cfg(test)]
+fn test_level_encoded(data_attributes: &str, special_chunk: &str) -> String {
+    let floor_chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}{}</chunk>",
+        ["0,"; 240].concat(),
+        ["1"; 16].join(",")
+    );
+    test_level_layers(&floor_chunk, data_attributes, special_chunk)
+}
+/// Builds a level out of a chunk for the collision layer and one for the special layer, leaving the others empty
+#[cfg(test)]
+fn test_level_layers(collision_chunk: &str, data_attributes: &str, special_chunk: &str) -> String {
+    let empty_chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        ["0"; 256].join(",")
+    );
+    let mut layers = String::new();
+    for (i, name) in ["Background", "Collision", "Details", "Special"]
+        .iter()
+        .enumerate()
+    {
+        let (attributes, chunk) = match i {
+            1 => ("encoding=\"csv\"", collision_chunk),
+            3 => (data_attributes, special_chunk),
+            _ => ("encoding=\"csv\"", empty_chunk.as_str()),
+        };
+        layers +=
+            &format!("<layer id=\"{i}\" name=\"{name}\"><data {attributes}>{chunk}</data></layer>");
+    }
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><map infinite=\"1\">{layers}</map>")
+}
+#[cfg(test)]
+pub(crate) fn test_level(special_chunk: &str) -> String {
+    test_level_encoded("encoding=\"csv\"", special_chunk)
+}
+/// Builds a level out of a map 16 tiles wide and up to 16 tall, with a character for each tile.
+/// The map is placed at the bottom of the level.
+/// `#` is solid, `/` and `\` are slopes, `-` is a one-way platform, `c` is a climbable wall, `s` is a spring, `o` is a lasso target, `h` is a horse and `>` is an arrow for it.
+#[cfg(test)]
+pub(crate) fn test_map(rows: &[&str]) -> LevelData {
+    let mut collision = ["0"; 256];
+    let mut special = ["0"; 256];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, tile) in row.chars().enumerate() {
+            let i = x + (16 - rows.len() + y) * 16;
+            match tile {
+                '#' => collision[i] = "1",
+                '/' => collision[i] = "1859",
+                '\\' => collision[i] = "1860",
+                '-' => collision[i] = "1901",
+                'c' => (collision[i], special[i]) = ("1", "513"),
+                's' => collision[i] = "1895",
+                'o' => special[i] = "1",
+                'h' => special[i] = "385",
+                '>' => special[i] = "417",
+                _ => {}
+            }
+        }
+    }
+    let chunk = |tiles: [&str; 256]| {
+        format!(
+            "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+            tiles.join(",")
+        )
+    };
+    let level = test_level_layers(&chunk(collision), "encoding=\"csv\"", &chunk(special));
+    LevelData::parse(&level, "map.tmx".to_string()).unwrap_or_else(|f| panic!("{f}"))
+}
+
+#[test]
+fn shipped_levels_parse() {
+    let (worlds, levels) = crate::assets::load_tower(|name| {
+        crate::assets::LEVELS_DIR
+            .get_file(name)
+            .and_then(|f| f.contents_utf8())
+            .map(str::to_string)
+    })
+    .unwrap_or_else(|errors| {
+        let errors: Vec<String> = errors.iter().map(|f| f.to_string()).collect();
+        panic!("{}", errors.join("\n"))
+    });
+    assert_eq!(worlds.len(), 3);
+    assert_eq!(levels[0].name, "0-0.tmx");
+    for level in levels {
+        assert!(level.world < worlds.len());
+        assert!(
+            level.min_pos.x <= level.player_spawn.x
+                && level.player_spawn.x <= level.max_pos.x + 16.0 * 8.0
+        );
+        assert!(level.roof_height <= level.floor_height);
+    }
+}
+
+#[test]
+fn bad_levels_report_errors() {
+    let result = LevelData::parse("<map><layer>", "broken.tmx".to_string());
+    assert!(matches!(result, Err(LevelLoadError::MalformedXml { .. })));
+
+    let mut values = ["0"; 256];
+    values[17] = "oops";
+    let chunk = format!(
+        "<chunk x=\"16\" y=\"-16\" width=\"16\" height=\"16\">{}</chunk>",
+        values.join(",")
+    );
+    let result = LevelData::parse(&test_level(&chunk), "bad_tile.tmx".to_string());
+    let Err(LevelLoadError::BadTileValue {
+        level,
+        chunk,
+        index,
+        ..
+    }) = result
+    else {
+        panic!("expected BadTileValue");
+    };
+    assert_eq!(
+        (level.as_str(), chunk, index),
+        ("bad_tile.tmx", (16, -16), 17)
+    );
+
+    let chunk = "<chunk y=\"0\" width=\"16\" height=\"16\">0</chunk>";
+    let result = LevelData::parse(&test_level(chunk), "no_x.tmx".to_string());
+    assert!(matches!(
+        result,
+        Err(LevelLoadError::BadChunkAttribute { attribute: "x", .. })
+    ));
+
+    let mut values = ["0"; 256];
+    values[0] = "10";
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        values.join(",")
+    );
+    let result = LevelData::parse(&test_level(&chunk), "unknown_enemy.tmx".to_string());
+    assert!(matches!(
+        result,
+        Err(LevelLoadError::UnknownEnemy { id: 9, .. })
+    ));
+
+    // henry, with every marker he needs but the last
+    let mut values = ["0"; 256];
+    values[0] = "929";
+    values[1..5].copy_from_slice(&["897", "898", "899", "900"]);
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        values.join(",")
+    );
+    let result = LevelData::parse(&test_level(&chunk), "missing_marker.tmx".to_string());
+    assert!(matches!(
+        result,
+        Err(LevelLoadError::MissingMarker {
+            marker: "4",
+            required_by: "Henry",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn all_layer_encodings_decode() {
+    use std::io::Write;
+
+    let mut tiles = [0_u32; 256];
+    tiles[0] = 1;
+    tiles[37] = 1024 + 1;
+    tiles[255] = 448 + 1;
+    let bytes: Vec<u8> = tiles.iter().flat_map(|f| f.to_le_bytes()).collect();
+    let chunk = |text: String| {
+        format!("<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">\n{text}\n</chunk>")
+    };
+
+    let csv = chunk(tiles.map(|f| f.to_string()).join(","));
+    let xml = chunk(tiles.map(|f| format!("<tile gid=\"{f}\"/>")).concat());
+    let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    zlib.write_all(&bytes).unwrap();
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(&bytes).unwrap();
+    let zstd =
+        ruzstd::encoding::compress_to_vec(&bytes[..], ruzstd::encoding::CompressionLevel::Fastest);
+
+    let expected = LevelData::parse(&test_level(&csv), "csv.tmx".to_string()).unwrap();
+    for (attributes, chunk) in [
+        ("", xml),
+        ("encoding=\"base64\"", chunk(BASE64_STANDARD.encode(&bytes))),
+        (
+            "encoding=\"base64\" compression=\"zlib\"",
+            chunk(BASE64_STANDARD.encode(zlib.finish().unwrap())),
+        ),
+        (
+            "encoding=\"base64\" compression=\"gzip\"",
+            chunk(BASE64_STANDARD.encode(gzip.finish().unwrap())),
+        ),
+        (
+            "encoding=\"base64\" compression=\"zstd\"",
+            chunk(BASE64_STANDARD.encode(zstd)),
+        ),
+    ] {
+        let level = LevelData::parse(
+            &test_level_encoded(attributes, &chunk),
+            attributes.to_string(),
+        )
+        .unwrap_or_else(|f| panic!("{f}"));
+        assert_eq!(level.data, expected.data, "{attributes}");
+        assert_eq!(level.forced_player_spawn, expected.forced_player_spawn);
+    }
+
+    let result = LevelData::parse(
+        &test_level_encoded(
+            "encoding=\"base64\" compression=\"zlib\"",
+            &chunk("AAAA".to_string()),
+        ),
+        "bad_zlib.tmx".to_string(),
+    );
+    assert!(matches!(result, Err(LevelLoadError::BadChunkData { .. })));
+}
+
+#[test]
+fn flipped_tiles_keep_their_flags() {
+    let mut tiles = [0_u32; 256];
+    // lasso target, flipped horizontally
+    tiles[0] = 0x80000000 + 1;
+    // player spawn, rotated a quarter turn (diagonal + horizontal)
+    tiles[255] = 0xA0000000 + 448 + 1;
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        tiles.map(|f| f.to_string()).join(",")
+    );
+    let level = LevelData::parse(&test_level(&chunk), "flipped.tmx".to_string()).unwrap();
+
+    assert_eq!(level.data[0][3], 1);
+    assert_eq!(level.lasso_targets, vec![vec2(4.0, 4.0)]);
+    let flip = TileFlip {
+        horizontal: true,
+        ..Default::default()
+    };
+    assert_eq!(level.flips[0][3], flip);
+    assert_eq!(level.flips[0][2], flip);
+
+    assert_eq!(level.data[255][3], 448 + 1);
+    assert!(level.forced_player_spawn.is_some());
+    assert_eq!(
+        level.flips[255][3],
+        TileFlip {
+            horizontal: true,
+            vertical: false,
+            diagonal: true,
+        }
+    );
+
+    // the flags are stripped from the id, but values that still don't fit are rejected
+    let chunk = chunk.replacen("2147483649", "65536", 1);
+    let result = LevelData::parse(&test_level(&chunk), "too_big.tmx".to_string());
+    assert!(matches!(
+        result,
+        Err(LevelLoadError::BadTileValue { index: 0, .. })
+    ));
+}
+
+#[test]
+fn objects_are_applied() {
+    let mut tiles = ["0"; 256];
+    // an enemy at (16, 80)
+    tiles[16 * 10 + 2] = "2";
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        tiles.join(",")
+    );
+    let with_objects = |objects: &str| {
+        test_level(&chunk).replace(
+            "</map>",
+            &format!("<objectgroup id=\"5\" name=\"Objects\">{objects}</objectgroup></map>"),
+        )
+    };
+    let level = LevelData::parse(
+        &with_objects(
+            r#"<object id="1" type="player_spawn" x="3" y="5"><point/></object>
+            <object id="2" type="enemy_path" x="16" y="80"><polyline points="0,0 32,0"/></object>
+            <object id="3" type="enemy" x="20" y="84"><properties><property name="attack_delay" type="float" value="1.5"/></properties><point/></object>
+            <object id="4" type="camera_zone" x="0" y="0" width="64" height="32"><properties><property name="offset" type="float" value="-16"/></properties></object>
+            <object id="5" name="door" x="41" y="42"><point/></object>"#,
+        ),
+        "objects.tmx".to_string(),
+    )
+    .unwrap_or_else(|f| panic!("{f}"));
+
+    assert_eq!(level.forced_player_spawn, Some(vec2(0.0, 0.0)));
+    assert_eq!(level.enemy_paths.len(), 1);
+    // walked there and back, one point per tile
+    assert_eq!(level.enemy_paths[0].len(), 8);
+    assert_eq!(level.enemy_paths[0][4], vec2(48.0, 80.0));
+    assert_eq!(level.enemies[0].path_index, Some((0, 0)));
+    assert_eq!(level.enemies[0].attack_delay, 1.5);
+    assert_eq!(
+        level.camera_zones,
+        vec![(Rect::new(0.0, 0.0, 64.0, 32.0), -16.0)]
+    );
+    assert_eq!(level.find_marker("door"), Some(vec2(40.0, 40.0)));
+
+    for objects in [
+        r#"<object id="7" type="camera_zone" x="0" y="0" width="8" height="8"/>"#,
+        r#"<object id="7" type="enemy" x="100" y="0"><point/></object>"#,
+        r#"<object id="7" type="enemy_path" x="0" y="0"><polyline points="0,0 nope"/></object>"#,
+    ] {
+        let result = LevelData::parse(&with_objects(objects), "bad_objects.tmx".to_string());
+        assert!(
+            matches!(result, Err(LevelLoadError::BadObject { object: 7, .. })),
+            "{objects}"
+        );
+    }
+}
+
+#[test]
+fn moving_platforms_follow_paths() {
+    let mut tiles = ["0"; 256];
+    // a path from (16, 40) to (48, 40), with a platform above its left end
+    for x in 2..7 {
+        tiles[5 * 16 + x] = "481";
+    }
+    tiles[4 * 16 + 2] = "1902";
+    tiles[4 * 16 + 3] = "1903";
+    // a platform with no path
+    tiles[10 * 16 + 10] = "1902";
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        tiles.join(",")
+    );
+    let level = LevelData::parse(
+        &test_level(&chunk).replace(
+            "</map>",
+            r#"<objectgroup id="5" name="Objects"><object id="1" type="moving_platform" x="0" y="96"><properties><property name="width" type="int" value="3"/><property name="mode" value="loop"/><property name="lasso" type="bool" value="true"/></properties><polyline points="0,0 16,0"/></object></objectgroup></map>"#,
+        ),
+        "platforms.tmx".to_string(),
+    )
+    .unwrap_or_else(|f| panic!("{f}"));
+
+    let platforms = &level.moving_platforms;
+    assert_eq!(platforms.len(), 2);
+    assert_eq!(platforms[0].tiles, vec![1902, 1903]);
+    assert_eq!(platforms[0].pos_at(0.0), vec2(16.0, 32.0));
+    // walked there and back
+    assert_eq!(platforms[0].path.len(), 8);
+    assert_eq!(platforms[0].pos_at(2.0), vec2(48.0, 32.0));
+    assert_eq!(platforms[0].pos_at(4.0), vec2(16.0, 32.0));
+
+    assert_eq!(platforms[1].tiles, vec![1902, 1903, 1902]);
+    assert_eq!(
+        platforms[1].path,
+        vec![
+            vec2(0.0, 96.0),
+            vec2(8.0, 96.0),
+            vec2(16.0, 96.0),
+            vec2(8.0, 96.0)
+        ]
+    );
+    assert_eq!(platforms[1].lasso_target(0.0), Some(vec2(12.0, 108.0)));
+
+    let warnings: Vec<Vec2> = level.lint().iter().map(|f| f.pos).collect();
+    assert_eq!(warnings, vec![vec2(80.0, 80.0)]);
+}
+
+#[test]
+fn explosions_break_tiles_until_restart() {
+    let mut data = LevelData::parse(&test_level(""), "breakable.tmx".to_string()).unwrap();
+    // cracked bricks in the floor, from (16, 120) to (40, 120)
+    for x in 2..6 {
+        data.data[15 * 16 + x][1] = 1881;
+    }
+    let mut level = Level::new(data);
+    let broken = level.break_tiles(vec2(28.0, 124.0), 10.0);
+    assert_eq!(
+        broken,
+        vec![
+            (vec2(16.0, 120.0), 1881),
+            (vec2(24.0, 120.0), 1881),
+            (vec2(32.0, 120.0), 1881)
+        ]
+    );
+    assert!(!level.collides_at(vec2(20.0, 124.0)));
+    assert!(level.collides_at(vec2(44.0, 124.0)));
+    // unbreakable floor is left alone
+    assert!(level.collides_at(vec2(12.0, 124.0)));
+
+    level.restart();
+    assert!(level.collides_at(vec2(20.0, 124.0)));
+    assert!(level.break_tiles(vec2(0.0, 0.0), 8.0).is_empty());
+}
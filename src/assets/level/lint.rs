@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use macroquad::prelude::*;
+
+use crate::{
+    assets::{LevelData, TILES, TileKind, level::chunk_of},
+    enemies::EnemySpawner,
+};
+
+/// A problem with a level that doesn't stop it from loading, but is most likely a mistake
+#[derive(Clone, Debug)]
+pub struct LevelWarning {
+    pub pos: Vec2,
+    pub message: String,
+}
+impl LevelWarning {
+    pub fn new(pos: Vec2, message: String) -> Self {
+        Self { pos, message }
+    }
+}
+impl Display for LevelWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (chunk, tile) = chunk_of(self.pos);
+        write!(f, "chunk {chunk:?}, tile {tile:?}: {}", self.message)
+    }
+}
+
+impl LevelData {
+    /// Checks the level for likely mistakes, including the warnings found while parsing it.
+    pub fn lint(&self) -> Vec<LevelWarning> {
+        let mut warnings = self.warnings.clone();
+        let index_pos = |i: usize| {
+            vec2(
+                ((i % self.width) * 8) as f32 + self.min_pos.x,
+                ((i / self.width) * 8) as f32 + self.min_pos.y,
+            )
+        };
+
+        for (index, path) in self.enemy_paths.iter().enumerate() {
+            if !self
+                .enemies
+                .iter()
+                .any(|f| f.path_index.is_some_and(|f| f.0 == index))
+                && !self
+                    .moving_platforms
+                    .iter()
+                    .any(|f| f.path_index == Some(index))
+            {
+                warnings.push(LevelWarning::new(
+                    path[0],
+                    "enemy path doesn't touch any enemy".to_string(),
+                ));
+            }
+        }
+
+        let mut triggers = Vec::new();
+        for (i, tile) in self.data.iter().enumerate() {
+            let kind = TILES.get(tile[3]).kind;
+            if let TileKind::Spawner | TileKind::TriggerSpawner(_) = kind {
+                let x = i % self.width;
+                let y = i / self.width;
+                let has_enemy = [(1, 0), (0, 1), (-1, 0), (0, -1)].iter().any(|d| {
+                    // neighbours outside the level don't wrap around to the other side
+                    let (Some(nx), Some(ny)) =
+                        (x.checked_add_signed(d.0), y.checked_add_signed(d.1))
+                    else {
+                        return false;
+                    };
+                    nx < self.width
+                        && self
+                            .data
+                            .get(nx + ny * self.width)
+                            .is_some_and(|f| matches!(TILES.get(f[3]).kind, TileKind::Enemy))
+                });
+                if !has_enemy {
+                    warnings.push(LevelWarning::new(
+                        index_pos(i),
+                        "spawner has no enemy next to it".to_string(),
+                    ));
+                }
+            } else if let TileKind::Trigger(id) = kind
+                && !triggers.iter().any(|f: &(u8, Vec2)| f.0 == id)
+            {
+                triggers.push((id, index_pos(i)));
+            }
+        }
+        for (area, id) in &self.trigger_zones {
+            triggers.push((*id, area.point()));
+        }
+        for (id, pos) in triggers {
+            if !self
+                .enemies
+                .iter()
+                .any(|f| matches!(f.spawner, Some(EnemySpawner::Trigger(trigger)) if trigger == id))
+            {
+                warnings.push(LevelWarning::new(
+                    pos,
+                    format!("trigger {id} doesn't spawn any enemy"),
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+#[test]
+fn mistakes_are_linted() {
+    let mut tiles = ["0"; 256];
+    // horse without arrow
+    tiles[2 * 16 + 2] = "385";
+    // spawner without enemy
+    tiles[2 * 16 + 6] = "482";
+    // trigger without spawner
+    tiles[2 * 16 + 10] = "609";
+    // enemy path without enemy
+    tiles[6 * 16 + 2] = "481";
+    // number tile without anything to attach to
+    tiles[6 * 16 + 6] = "993";
+    // spawner on the edge, with an enemy at the start of the next row
+    tiles[8 * 16 + 15] = "482";
+    tiles[9 * 16] = "2";
+    let chunk = format!(
+        "<chunk x=\"0\" y=\"0\" width=\"16\" height=\"16\">{}</chunk>",
+        tiles.join(",")
+    );
+    let level = LevelData::parse(
+        &crate::assets::level::test_level(&chunk),
+        "lint.tmx".to_string(),
+    )
+    .unwrap();
+    let mut warnings: Vec<(Vec2, String)> = level
+        .lint()
+        .into_iter()
+        .map(|f| (f.pos, f.message))
+        .collect();
+    warnings.sort_by(|a, b| (a.0.y, a.0.x).partial_cmp(&(b.0.y, b.0.x)).unwrap());
+    let positions: Vec<Vec2> = warnings.iter().map(|f| f.0).collect();
+    assert_eq!(
+        positions,
+        vec![
+            vec2(16.0, 16.0),
+            vec2(48.0, 16.0),
+            vec2(80.0, 16.0),
+            vec2(16.0, 48.0),
+            vec2(48.0, 48.0),
+            vec2(120.0, 64.0)
+        ],
+        "{warnings:?}"
+    );
+}
@@ -0,0 +1,423 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use macroquad::prelude::*;
+
+use crate::assets::{CollisionShape, LevelData, TILES};
+
+/// Highest jump links go, in tiles
+pub const MAX_JUMP_HEIGHT: i16 = 4;
+/// Widest gap jump links go across, in tiles
+pub const MAX_JUMP_GAP: i16 = 3;
+/// Furthest drop and jump links go down, in tiles
+pub const MAX_DROP: i16 = 8;
+/// How many tiles a search for a flying path looks at before giving up
+const MAX_FLYING_SEARCH: usize = 2048;
+
+/// The tile a body is in, by its centre
+pub fn tile_of(pos: Vec2) -> (i16, i16) {
+    let tile = ((pos + 4.0) / 8.0).floor();
+    (tile.x as i16, tile.y as i16)
+}
+
+/// A row of tiles next to each other that a body can stand in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub row: i16,
+    /// Leftmost column of the span
+    pub start: i16,
+    /// Rightmost column of the span
+    pub end: i16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkKind {
+    /// Walking onto a span a tile higher or lower, like up stairs or slopes
+    Step,
+    /// Walking off a ledge and falling onto a span below
+    Drop,
+    /// Jumping across a gap, or up onto a span
+    Jump,
+}
+
+/// A way of getting from one span to another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NavLink {
+    pub from: usize,
+    pub to: usize,
+    pub kind: LinkKind,
+    /// Column in `from` the link starts at
+    pub takeoff: i16,
+    /// Column in `to` the link ends at
+    pub landing: i16,
+    /// How many tiles higher the link ends than it starts. Negative when going down.
+    pub rise: i16,
+}
+
+/// Where bodies walking through a level can stand, and how they can get between those places.
+/// Built from the level's collision layer.
+#[derive(Debug, Default)]
+pub struct Navigation {
+    pub spans: Vec<Span>,
+    pub links: Vec<NavLink>,
+    /// Indices into `links` of the links starting at each span
+    outgoing: Vec<Vec<usize>>,
+    /// Which span each tile a body can stand in belongs to
+    span_tiles: HashMap<(i16, i16), usize>,
+}
+
+impl LevelData {
+    /// Whether a body can't be in the tile at all, because it's solid
+    fn blocks_bodies(&self, x: i16, y: i16) -> bool {
+        let tile = self.get_tile(x, y)[1];
+        let properties = TILES.get(tile);
+        tile != 0
+            && !properties.deadly
+            && matches!(
+                properties.collision,
+                CollisionShape::Full | CollisionShape::TopHalf
+            )
+    }
+    /// Whether bodies standing on the tile are inside it, like on slopes and bottom halves
+    fn is_stood_in(&self, x: i16, y: i16) -> bool {
+        let tile = self.get_tile(x, y)[1];
+        let properties = TILES.get(tile);
+        tile != 0
+            && !properties.deadly
+            && !matches!(
+                properties.collision,
+                CollisionShape::Full | CollisionShape::TopHalf | CollisionShape::Platform
+            )
+    }
+    /// Whether a body can stand in the tile, tall bodies taking up the tile above too
+    fn can_stand_in(&self, x: i16, y: i16) -> bool {
+        if self.blocks_bodies(x, y) || self.blocks_bodies(x, y - 1) {
+            return false;
+        }
+        let below = self.get_tile(x, y + 1)[1];
+        self.is_stood_in(x, y)
+            || (below != 0 && !TILES.get(below).deadly && !self.is_stood_in(x, y + 1))
+    }
+    fn is_deadly(&self, x: i16, y: i16) -> bool {
+        TILES.get(self.get_tile(x, y)[1]).deadly
+    }
+    /// Finds the shortest way through tiles without anything solid in them, for a flying body a tile big.
+    /// Returns every tile on the way, starting with `from`.
+    pub fn find_flying_path(&self, from: (i16, i16), to: (i16, i16)) -> Option<Vec<(i16, i16)>> {
+        let is_empty = |(x, y): (i16, i16)| {
+            let tile = self.get_tile(x, y)[1];
+            tile == 0 || TILES.get(tile).deadly
+        };
+        if !is_empty(to) {
+            return None;
+        }
+        let estimate = |(x, y): (i16, i16)| {
+            (x - to.0).unsigned_abs() as u32 + (y - to.1).unsigned_abs() as u32
+        };
+        let mut came_from = HashMap::new();
+        let mut costs = HashMap::from([(from, 0)]);
+        let mut open = BinaryHeap::from([Reverse((estimate(from), from))]);
+        while let Some(Reverse((_, tile))) = open.pop() {
+            if tile == to {
+                let mut path = vec![tile];
+                while let Some(&previous) = came_from.get(path.last().unwrap()) {
+                    path.push(previous);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if costs.len() > MAX_FLYING_SEARCH {
+                return None;
+            }
+            let cost = costs[&tile] + 1;
+            for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+                let next = (tile.0 + dx, tile.1 + dy);
+                if !is_empty(next) || costs.get(&next).is_some_and(|f| *f <= cost) {
+                    continue;
+                }
+                costs.insert(next, cost);
+                came_from.insert(next, tile);
+                open.push(Reverse((cost + estimate(next), next)));
+            }
+        }
+        None
+    }
+}
+
+impl Navigation {
+    pub fn new(level: &LevelData) -> Self {
+        let min_x = (level.min_pos.x / 8.0) as i16;
+        let min_y = (level.min_pos.y / 8.0) as i16;
+        let width = level.width as i16;
+        let height = (level.data.len() / level.width) as i16;
+
+        let mut navigation = Self::default();
+        for y in min_y..min_y + height {
+            let mut x = min_x;
+            while x < min_x + width {
+                if !level.can_stand_in(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < min_x + width && level.can_stand_in(x, y) {
+                    navigation.span_tiles.insert((x, y), navigation.spans.len());
+                    x += 1;
+                }
+                navigation.spans.push(Span {
+                    row: y,
+                    start,
+                    end: x - 1,
+                });
+            }
+        }
+
+        for (index, span) in navigation.spans.iter().enumerate() {
+            let mut links = Vec::new();
+            // walking off either end
+            for (edge, direction) in [(span.start, -1), (span.end, 1)] {
+                let next = edge + direction;
+                let link = |to: usize, row: i16, kind: LinkKind| NavLink {
+                    from: index,
+                    to,
+                    kind,
+                    takeoff: edge,
+                    landing: next,
+                    rise: span.row - row,
+                };
+                // bodies can only walk up or down a whole tile on slopes and half tiles.
+                // other spans a tile away are jumped up to or dropped down to.
+                let walkable = level.is_stood_in(edge, span.row);
+                if let Some(step) = [span.row - 1, span.row + 1]
+                    .into_iter()
+                    .filter(|row| walkable || level.is_stood_in(next, *row))
+                    .find_map(|row| Some((*navigation.span_tiles.get(&(next, row))?, row)))
+                {
+                    links.push(link(step.0, step.1, LinkKind::Step));
+                } else if !level.blocks_bodies(next, span.row)
+                    && !level.blocks_bodies(next, span.row - 1)
+                {
+                    let landing = (span.row + 1..=span.row + MAX_DROP)
+                        .take_while(|row| {
+                            !level.is_deadly(next, *row) && !level.blocks_bodies(next, *row)
+                        })
+                        .find_map(|row| Some((*navigation.span_tiles.get(&(next, row))?, row)));
+                    if let Some((to, row)) = landing {
+                        links.push(link(to, row, LinkKind::Drop));
+                    }
+                }
+            }
+            // jumping onto other spans
+            for (to, other) in navigation.spans.iter().enumerate() {
+                let rise = span.row - other.row;
+                if to == index
+                    || !(-MAX_DROP..=MAX_JUMP_HEIGHT).contains(&rise)
+                    || links.iter().any(|f| f.to == to)
+                {
+                    continue;
+                }
+                let top = span.row.min(other.row) - 1;
+                let is_clear = |x: i16, rows: std::ops::RangeInclusive<i16>| {
+                    rows.into_iter().all(|y| !level.blocks_bodies(x, y))
+                };
+                let columns = if other.start <= span.end && span.start <= other.end {
+                    // straight up, through a platform
+                    if rise <= 0 {
+                        continue;
+                    }
+                    let overlap = other.start.max(span.start)..=other.end.min(span.end);
+                    let Some(x) = overlap.into_iter().find(|x| is_clear(*x, top..=span.row)) else {
+                        continue;
+                    };
+                    (x, x)
+                } else {
+                    let (takeoff, landing) = if other.start > span.end {
+                        (span.end, other.start)
+                    } else {
+                        (span.start, other.end)
+                    };
+                    let direction = (landing - takeoff).signum();
+                    if (landing - takeoff).abs() - 1 > MAX_JUMP_GAP
+                        || !is_clear(takeoff, top..=span.row)
+                        || !is_clear(landing, top..=other.row)
+                        || !(1..(landing - takeoff).abs())
+                            .all(|i| is_clear(takeoff + i * direction, top..=top + 1))
+                    {
+                        continue;
+                    }
+                    (takeoff, landing)
+                };
+                links.push(NavLink {
+                    from: index,
+                    to,
+                    kind: LinkKind::Jump,
+                    takeoff: columns.0,
+                    landing: columns.1,
+                    rise,
+                });
+            }
+            navigation.outgoing.push(
+                (0..links.len())
+                    .map(|f| f + navigation.links.len())
+                    .collect(),
+            );
+            navigation.links.extend(links);
+        }
+        navigation
+    }
+    /// Finds the span a body in `tile` is standing in
+    pub fn span_at(&self, tile: (i16, i16)) -> Option<usize> {
+        self.span_tiles.get(&tile).copied()
+    }
+    /// Finds the span a body in `tile` would land in, if it fell straight down
+    pub fn span_below(&self, tile: (i16, i16)) -> Option<usize> {
+        (tile.1..=tile.1 + MAX_DROP).find_map(|y| self.span_at((tile.0, y)))
+    }
+    /// Finds the cheapest links to follow from standing in `column` of the span `from`, to get to the span `to`.
+    /// Links `can_use` returns false for are skipped.
+    pub fn find_route(
+        &self,
+        from: usize,
+        column: i16,
+        to: usize,
+        can_use: impl Fn(&NavLink) -> bool,
+    ) -> Option<Vec<usize>> {
+        // (cost, column the span was entered at, link it was entered through)
+        let mut best: HashMap<usize, (i32, i16, Option<usize>)> =
+            HashMap::from([(from, (0, column, None))]);
+        let mut open = BinaryHeap::from([Reverse((0, from))]);
+        while let Some(Reverse((cost, span))) = open.pop() {
+            if span == to {
+                let mut route = Vec::new();
+                let mut current = span;
+                while let Some(link) = best[&current].2 {
+                    route.push(link);
+                    current = self.links[link].from;
+                }
+                route.reverse();
+                return Some(route);
+            }
+            let (_, entry, _) = best[&span];
+            if cost > best[&span].0 {
+                continue;
+            }
+            for &index in &self.outgoing[span] {
+                let link = &self.links[index];
+                if !can_use(link) {
+                    continue;
+                }
+                let link_cost = match link.kind {
+                    LinkKind::Step | LinkKind::Drop => 1,
+                    LinkKind::Jump => 4,
+                };
+                let cost = cost
+                    + (entry - link.takeoff).abs() as i32
+                    + (link.takeoff - link.landing).abs() as i32
+                    + link_cost;
+                if best.get(&link.to).is_some_and(|f| f.0 <= cost) {
+                    continue;
+                }
+                best.insert(link.to, (cost, link.landing, Some(index)));
+                open.push(Reverse((cost, link.to)));
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn spans_are_linked_by_steps_drops_and_jumps() {
+    use crate::assets::test_map;
+
+    let level = test_map(&[
+        "          ------",
+        "                ",
+        "                ",
+        "#####   ########",
+        "#####  #########",
+        "###    #########",
+        "################",
+    ]);
+    let navigation = Navigation::new(&level);
+    let spans: Vec<(i16, i16, i16)> = navigation
+        .spans
+        .iter()
+        .map(|f| (f.row, f.start, f.end))
+        .collect();
+    assert_eq!(
+        spans,
+        [(8, 10, 15), (11, 0, 4), (11, 8, 15), (12, 7, 7), (14, 5, 6)]
+    );
+    let link = |from: usize, to: usize| {
+        navigation
+            .links
+            .iter()
+            .find(|f| f.from == from && f.to == to)
+            .map(|f| (f.kind, f.takeoff, f.landing))
+    };
+    // down and up a tile
+    assert_eq!(link(2, 3), Some((LinkKind::Drop, 8, 7)));
+    assert_eq!(link(3, 2), Some((LinkKind::Jump, 7, 8)));
+    // off the ledge
+    assert_eq!(link(1, 4), Some((LinkKind::Drop, 4, 5)));
+    // across the gap, and up through the platform
+    assert_eq!(link(1, 2), Some((LinkKind::Jump, 4, 8)));
+    assert_eq!(link(2, 0), Some((LinkKind::Jump, 10, 10)));
+    // too high to jump up to
+    assert_eq!(link(4, 0), None);
+
+    let route = |can_use: fn(&NavLink) -> bool| {
+        let route = navigation.find_route(4, 5, 0, can_use)?;
+        Some(
+            route
+                .iter()
+                .map(|f| navigation.links[*f].to)
+                .collect::<Vec<_>>(),
+        )
+    };
+    assert_eq!(route(|_| true), Some(vec![3, 0]));
+    // lower jumpers get up in two jumps
+    assert_eq!(route(|f| f.rise <= 3), Some(vec![2, 0]));
+    // the bottom of the pit can only be left by jumping
+    assert_eq!(route(|f| f.kind != LinkKind::Jump), None);
+
+    // slopes are walked up and down
+    let navigation = Navigation::new(&test_map(&["       /########", "################"]));
+    assert_eq!(
+        navigation.spans,
+        [
+            Span {
+                row: 13,
+                start: 8,
+                end: 15
+            },
+            Span {
+                row: 14,
+                start: 0,
+                end: 7
+            }
+        ]
+    );
+    assert_eq!(navigation.links[0].kind, LinkKind::Step);
+    assert_eq!(navigation.links[1].kind, LinkKind::Step);
+}
+
+#[test]
+fn flying_paths_go_around_walls() {
+    let level = crate::assets::test_map(&[
+        "                ",
+        "   #            ",
+        "   #            ",
+        "   #            ",
+        "################",
+    ]);
+    let path = level.find_flying_path((1, 14), (5, 14)).unwrap();
+    assert_eq!(path.first(), Some(&(1, 14)));
+    assert_eq!(path.last(), Some(&(5, 14)));
+    // over the top of the wall
+    assert!(path.contains(&(3, 11)));
+    assert_eq!(path.len(), 11);
+    assert_eq!(level.find_flying_path((1, 14), (3, 14)), None);
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, str::FromStr};
+
+use macroquad::prelude::*;
+
+use crate::{
+    assets::{
+        DEFAULT_PLATFORM_SPEED, LevelData, LevelLoadError, MOVING_PLATFORM_TILES, MovingPlatform,
+    },
+    enemies::EnemySpawner,
+};
+
+pub enum ObjectShape {
+    Rectangle,
+    Point,
+    Polyline(Vec<Vec2>),
+    Polygon(Vec<Vec2>),
+}
+
+/// An object from one of a level's object layers
+pub struct LevelObject {
+    pub id: u32,
+    pub name: String,
+    /// The object's class (saved as `type` by Tiled)
+    pub class: String,
+    pub pos: Vec2,
+    pub size: Vec2,
+    pub shape: ObjectShape,
+    pub properties: HashMap<String, String>,
+}
+impl LevelObject {
+    /// The area covered by the object. Points cover the tile they're in.
+    pub fn area(&self) -> Rect {
+        match self.shape {
+            ObjectShape::Point => {
+                let pos = snap_to_tile(self.pos);
+                Rect::new(pos.x, pos.y, 8.0, 8.0)
+            }
+            _ => Rect::new(self.pos.x, self.pos.y, self.size.x, self.size.y),
+        }
+    }
+    pub fn property<T: FromStr>(
+        &self,
+        name: &str,
+        level: &str,
+    ) -> Result<Option<T>, LevelLoadError> {
+        self.properties
+            .get(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    self.error(
+                        level,
+                        format!("property {name:?} has invalid value {value:?}"),
+                    )
+                })
+            })
+            .transpose()
+    }
+    fn required_property<T: FromStr>(&self, name: &str, level: &str) -> Result<T, LevelLoadError> {
+        self.property(name, level)?.ok_or_else(|| {
+            self.error(
+                level,
+                format!("{} is missing the property {name:?}", self.class),
+            )
+        })
+    }
+    fn error(&self, level: &str, reason: String) -> LevelLoadError {
+        LevelLoadError::BadObject {
+            level: level.to_string(),
+            object: self.id,
+            reason,
+        }
+    }
+}
+
+/// Rounds a position down to the top left corner of the tile it's in
+fn snap_to_tile(pos: Vec2) -> Vec2 {
+    (pos / 8.0).floor() * 8.0
+}
+
+pub fn parse_objects(
+    map: roxmltree::Node,
+    level: &str,
+) -> Result<Vec<LevelObject>, LevelLoadError> {
+    let mut objects = Vec::new();
+    for object in map
+        .children()
+        .filter(|f| f.has_tag_name("objectgroup"))
+        .flat_map(|f| f.children().filter(|f| f.has_tag_name("object")))
+    {
+        let id = object
+            .attribute("id")
+            .and_then(|f| f.parse().ok())
+            .unwrap_or_default();
+        let bad = |reason: String| LevelLoadError::BadObject {
+            level: level.to_string(),
+            object: id,
+            reason,
+        };
+        let number = |attribute: &str| -> Result<f32, LevelLoadError> {
+            match object.attribute(attribute) {
+                None => Ok(0.0),
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| bad(format!("invalid {attribute} {value:?}"))),
+            }
+        };
+        let mut pos = vec2(number("x")?, number("y")?);
+        let size = vec2(number("width")?, number("height")?);
+        // tile objects are positioned by their bottom left corner
+        if object.has_attribute("gid") {
+            pos.y -= size.y;
+        }
+
+        let shape = if let Some(line) = object
+            .children()
+            .find(|f| f.has_tag_name("polyline") || f.has_tag_name("polygon"))
+        {
+            let points = line
+                .attribute("points")
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|f| {
+                    let (x, y) = f.split_once(',')?;
+                    Some(pos + vec2(x.parse().ok()?, y.parse().ok()?))
+                })
+                .collect::<Option<Vec<Vec2>>>()
+                .filter(|f| !f.is_empty())
+                .ok_or_else(|| bad(format!("invalid {} points", line.tag_name().name())))?;
+            if line.has_tag_name("polyline") {
+                ObjectShape::Polyline(points)
+            } else {
+                ObjectShape::Polygon(points)
+            }
+        } else if object.children().any(|f| f.has_tag_name("point")) {
+            ObjectShape::Point
+        } else {
+            ObjectShape::Rectangle
+        };
+
+        let properties = object
+            .children()
+            .filter(|f| f.has_tag_name("properties"))
+            .flat_map(|f| f.children().filter(|f| f.has_tag_name("property")))
+            .filter_map(|f| {
+                let value = f.attribute("value").or(f.text()).unwrap_or_default();
+                Some((f.attribute("name")?.to_string(), value.to_string()))
+            })
+            .collect();
+
+        objects.push(LevelObject {
+            id,
+            name: object.attribute("name").unwrap_or_default().to_string(),
+            class: object
+                .attribute("type")
+                .or(object.attribute("class"))
+                .unwrap_or_default()
+                .to_string(),
+            pos,
+            size,
+            shape,
+            properties,
+        });
+    }
+    Ok(objects)
+}
+
+/// Splits a path into points 8 pixels apart, so enemies walk it at the same speed as a path made of tiles.
+///
+/// Open paths are walked back and forth, closed ones in a loop.
+fn resample_path(points: &[Vec2], closed: bool) -> Vec<Vec2> {
+    let mut corners = points.to_vec();
+    if closed {
+        corners.push(points[0]);
+    } else {
+        corners.extend(points.iter().rev().skip(1));
+    }
+    let mut path = vec![corners[0]];
+    // distance travelled since the last point was placed
+    let mut travelled = 0.0;
+    for segment in corners.windows(2) {
+        let length = segment[0].distance(segment[1]);
+        let mut along = 8.0 - travelled;
+        while along < length {
+            path.push(segment[0].lerp(segment[1], along / length));
+            along += 8.0;
+        }
+        travelled = length - (along - 8.0);
+    }
+    path
+}
+
+impl LevelData {
+    /// Applies objects from the level's object layers. These are applied after the special layer, so take precedence over its tiles.
+    pub(super) fn apply_objects(&mut self, objects: &[LevelObject]) -> Result<(), LevelLoadError> {
+        for object in objects {
+            if !object.name.is_empty() {
+                self.markers
+                    .insert(object.name.clone(), snap_to_tile(object.pos));
+            }
+            match object.class.as_str() {
+                "player_spawn" => self.forced_player_spawn = Some(snap_to_tile(object.pos)),
+                "level_end" => self.forced_level_end = Some(snap_to_tile(object.pos)),
+                "elevator_shaft" => {
+                    self.forced_level_elevator_shaft_height = Some(snap_to_tile(object.pos).y)
+                }
+                "enemy_path" => {
+                    let path = match &object.shape {
+                        ObjectShape::Polyline(points) => resample_path(points, false),
+                        ObjectShape::Polygon(points) => resample_path(points, true),
+                        _ => {
+                            return Err(object.error(
+                                &self.name,
+                                "enemy paths must be polylines or polygons".to_string(),
+                            ));
+                        }
+                    };
+                    let path_index = self.enemy_paths.len();
+                    for enemy in self.enemies.iter_mut() {
+                        let closest = path
+                            .iter()
+                            .map(|f| f.distance(enemy.pos))
+                            .enumerate()
+                            .filter(|f| f.1 < 8.0)
+                            .min_by(|a, b| a.1.total_cmp(&b.1));
+                        if let Some((index, _)) = closest {
+                            enemy.path_index = Some((path_index, index));
+                        }
+                    }
+                    self.enemy_paths.push(path);
+                }
+                "moving_platform" => {
+                    let points = match &object.shape {
+                        ObjectShape::Polyline(points) | ObjectShape::Polygon(points) => points,
+                        _ => {
+                            return Err(object.error(
+                                &self.name,
+                                "moving platforms must be polylines or polygons".to_string(),
+                            ));
+                        }
+                    };
+                    let mode: Option<String> = object.property("mode", &self.name)?;
+                    let closed = match mode.as_deref() {
+                        None => matches!(object.shape, ObjectShape::Polygon(_)),
+                        Some("loop") => true,
+                        Some("ping_pong") => false,
+                        Some(mode) => {
+                            return Err(object.error(&self.name, format!("unknown mode {mode:?}")));
+                        }
+                    };
+                    let width: usize = object.property("width", &self.name)?.unwrap_or(2);
+                    self.moving_platforms.push(MovingPlatform {
+                        path: resample_path(points, closed),
+                        start: 0,
+                        speed: object
+                            .property("speed", &self.name)?
+                            .unwrap_or(DEFAULT_PLATFORM_SPEED),
+                        tiles: (0..width.max(1))
+                            .map(|i| MOVING_PLATFORM_TILES[i % 2])
+                            .collect(),
+                        lasso: object.property("lasso", &self.name)?.unwrap_or(false),
+                        path_index: None,
+                    });
+                }
+                "camera_zone" => {
+                    let offset = object.required_property("offset", &self.name)?;
+                    self.camera_zones.push((object.area(), offset));
+                }
+                "trigger" => {
+                    let id = object.required_property("id", &self.name)?;
+                    self.trigger_zones.push((object.area(), id));
+                }
+                "enemy" => {
+                    let attack_delay: Option<f32> = object.property("attack_delay", &self.name)?;
+                    let trigger: Option<u8> = object.property("trigger", &self.name)?;
+                    let proximity: Option<bool> = object.property("proximity", &self.name)?;
+                    let area = object.area();
+                    let mut found = false;
+                    for enemy in self
+                        .enemies
+                        .iter_mut()
+                        .filter(|f| area.contains(f.pos + vec2(4.0, 4.0)))
+                    {
+                        found = true;
+                        if let Some(attack_delay) = attack_delay {
+                            enemy.attack_delay = attack_delay;
+                        }
+                        if let Some(trigger) = trigger {
+                            enemy.spawner = Some(EnemySpawner::Trigger(trigger));
+                        }
+                        if proximity == Some(true) {
+                            enemy.spawner = Some(EnemySpawner::Proximity);
+                        }
+                    }
+                    if !found {
+                        return Err(object.error(&self.name, "doesn't cover any enemy".to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use macroquad::prelude::*;
+
+use crate::assets::{Level, LevelData, LevelWarning, Spritesheet, TILES, TileKind};
+
+/// Speed of moving platforms placed with tiles, in tiles per second
+pub const DEFAULT_PLATFORM_SPEED: f32 = 2.0;
+/// The two halves of the moving platform tile, which moving platforms placed as objects alternate between
+pub const MOVING_PLATFORM_TILES: [u16; 2] = [1902, 1903];
+
+/// A one-way platform that moves along a path, carrying whatever stands on it
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovingPlatform {
+    /// Points 8 pixels apart that the platform's top left corner moves through, in a loop.
+    /// Paths walked back and forth include the way back.
+    pub path: Vec<Vec2>,
+    /// Index of the point in `path` the platform starts at
+    pub start: usize,
+    /// Tiles per second
+    pub speed: f32,
+    /// The tile drawn for each 8 pixels of the platform's width
+    pub tiles: Vec<u16>,
+    /// Whether the platform can be lassoed, from below its centre
+    pub lasso: bool,
+    /// The enemy path the platform was placed next to, if it was placed with tiles
+    pub path_index: Option<usize>,
+}
+impl MovingPlatform {
+    pub fn width(&self) -> f32 {
+        self.tiles.len() as f32 * 8.0
+    }
+    /// Position of the platform's top left corner, `time` seconds into the level
+    pub fn pos_at(&self, time: f32) -> Vec2 {
+        let value = (time * self.speed + self.start as f32).rem_euclid(self.path.len() as f32);
+        let index = value.floor();
+        let current = self.path[index as usize];
+        let next = self.path[(index as usize + 1) % self.path.len()];
+        current.lerp(next, value - index)
+    }
+    /// Where the platform can be lassoed, `time` seconds into the level
+    pub fn lasso_target(&self, time: f32) -> Option<Vec2> {
+        self.lasso
+            .then(|| self.pos_at(time) + vec2(self.width() / 2.0, 12.0))
+    }
+    /// Whether a body at `pos` is standing on the platform, as it was `time` seconds into the level
+    pub fn carries(&self, pos: Vec2, time: f32) -> bool {
+        let platform = self.pos_at(time);
+        (pos.y + 8.0 - platform.y).abs() <= 1.0
+            && pos.x + 8.0 > platform.x
+            && pos.x < platform.x + self.width()
+    }
+}
+
+/// Turns a path made of tiles into one that loops. Paths whose ends aren't next to each other are walked back and forth.
+fn loop_path(mut path: Vec<Vec2>) -> Vec<Vec2> {
+    let closed = path.len() > 2 && path[0].distance(path[path.len() - 1]) <= 8.0;
+    if !closed && path.len() > 1 {
+        let way_back: Vec<Vec2> = path[1..path.len() - 1].iter().rev().copied().collect();
+        path.extend(way_back);
+    }
+    path
+}
+
+impl LevelData {
+    /// Makes moving platforms out of rows of moving platform tiles on the special layer,
+    /// following the enemy path next to them.
+    pub(super) fn find_moving_platforms(&mut self) {
+        let height = self.data.len() / self.width;
+        let is_platform = |x: usize, y: usize| {
+            TILES.get(self.data[x + y * self.width][3]).kind == TileKind::MovingPlatform
+        };
+        let index_pos = |x: usize, y: usize| vec2((x * 8) as f32, (y * 8) as f32) + self.min_pos;
+        let mut platforms = Vec::new();
+        for y in 0..height {
+            let mut x = 0;
+            while x < self.width {
+                if !is_platform(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < self.width && is_platform(x, y) {
+                    x += 1;
+                }
+                let pos = index_pos(start, y);
+                let tiles = (start..x)
+                    .map(|x| self.data[x + y * self.width][3])
+                    .collect();
+                // find a path tile next to the platform
+                let attached = (start..x)
+                    .flat_map(|x| {
+                        let tile = index_pos(x, y);
+                        [(8.0, 0.0), (0.0, 8.0), (-8.0, 0.0), (0.0, -8.0)]
+                            .map(|(dx, dy)| tile + vec2(dx, dy))
+                    })
+                    .find_map(|neighbour| {
+                        self.enemy_paths
+                            .iter()
+                            .enumerate()
+                            .find_map(|(index, path)| {
+                                let point = path.iter().position(|f| *f == neighbour)?;
+                                Some((index, point, neighbour))
+                            })
+                    });
+                let Some((path_index, point, path_pos)) = attached else {
+                    self.warnings.push(LevelWarning::new(
+                        pos,
+                        "moving platform isn't next to an enemy path".to_string(),
+                    ));
+                    continue;
+                };
+                let offset = pos - path_pos;
+                platforms.push(MovingPlatform {
+                    path: loop_path(
+                        self.enemy_paths[path_index]
+                            .iter()
+                            .map(|f| *f + offset)
+                            .collect(),
+                    ),
+                    start: point,
+                    speed: DEFAULT_PLATFORM_SPEED,
+                    tiles,
+                    lasso: false,
+                    path_index: Some(path_index),
+                });
+            }
+        }
+        self.moving_platforms = platforms;
+    }
+}
+
+impl Level {
+    /// Draws the level's moving platforms where they currently are
+    pub fn draw_moving_platforms(&self, tileset: &Spritesheet) {
+        for platform in &self.moving_platforms {
+            let pos = platform.pos_at(self.time()).floor();
+            for (i, tile) in platform.tiles.iter().enumerate() {
+                let tile = tile - 1;
+                tileset.draw_tile(
+                    pos.x + i as f32 * 8.0,
+                    pos.y,
+                    (tile % 32) as f32,
+                    (tile / 32) as f32,
+                    None,
+                );
+            }
+            if let Some(target) = platform.lasso_target(self.time()) {
+                // drawn like a lasso target tile
+                let target = target.floor();
+                tileset.draw_tile(target.x - 4.0, target.y - 4.0, 0.0, 0.0, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+use crate::assets::{Level, LevelData, LevelLoadError};
+
+/// The file in the levels directory that describes the tower's worlds and levels
+pub const MANIFEST_FILE: &str = "tower.toml";
+
+#[derive(Deserialize)]
+struct Manifest {
+    worlds: Vec<WorldManifest>,
+}
+
+#[derive(Deserialize)]
+struct WorldManifest {
+    name: String,
+    palette: Palette,
+    elevator: usize,
+    levels: Vec<LevelManifest>,
+}
+
+#[derive(Deserialize)]
+struct LevelManifest {
+    file: String,
+    #[serde(default)]
+    end: LevelEnd,
+    boss: Option<String>,
+}
+
+/// Which side of a level its end (and the elevator) is on
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LevelEnd {
+    Left,
+    #[default]
+    Right,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Palette {
+    #[serde(deserialize_with = "hex_color")]
+    pub wall: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub border: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub shaft: Color,
+}
+
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    Ok(Color::from_hex(u32::deserialize(deserializer)?))
+}
+
+pub struct World {
+    pub name: String,
+    pub palette: Palette,
+    /// Index of the elevator animation used by the world
+    pub elevator: usize,
+}
+
+/// Loads the worlds and levels listed by the manifest, in order. `read_file` reads a file from the levels directory.
+///
+/// If anything fails to load, every error is returned instead of only the first.
+/// A manifest without any levels is an error too, as there would be nothing to play.
+pub fn load_tower(
+    read_file: impl Fn(&str) -> Option<String>,
+) -> Result<(Vec<World>, Vec<Level>), Vec<LevelLoadError>> {
+    let text = read_file(MANIFEST_FILE).ok_or_else(|| {
+        vec![LevelLoadError::MissingFile {
+            level: MANIFEST_FILE.to_string(),
+        }]
+    })?;
+    let manifest: Manifest = toml::from_str(&text).map_err(|error| {
+        vec![LevelLoadError::BadManifest {
+            level: MANIFEST_FILE.to_string(),
+            error,
+        }]
+    })?;
+    if manifest.worlds.iter().all(|f| f.levels.is_empty()) {
+        return Err(vec![LevelLoadError::NoLevels {
+            level: MANIFEST_FILE.to_string(),
+        }]);
+    }
+
+    let mut worlds = Vec::new();
+    let mut levels = Vec::new();
+    let mut errors = Vec::new();
+    for (world_index, world) in manifest.worlds.into_iter().enumerate() {
+        for entry in world.levels {
+            let Some(data) = read_file(&entry.file) else {
+                errors.push(LevelLoadError::MissingFile { level: entry.file });
+                continue;
+            };
+            let result = LevelData::parse(&data, entry.file).and_then(|mut level_data| {
+                if let Some(boss) = &entry.boss {
+                    level_data.set_boss(boss)?;
+                }
+                for warning in &level_data.warnings {
+                    warn!("{}: {warning}", level_data.name);
+                }
+                let mut level = Level::new(level_data);
+                level.world = world_index;
+                level.end = entry.end;
+                Ok(level)
+            });
+            match result {
+                Ok(level) => levels.push(level),
+                Err(error) => errors.push(error),
+            }
+        }
+        worlds.push(World {
+            name: world.name,
+            palette: world.palette,
+            elevator: world.elevator,
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok((worlds, levels))
+}
+
+#[test]
+fn bad_manifests_report_errors() {
+    let manifest = r#"
+        [[worlds]]
+        name = "Test"
+        palette = { wall = 0x000000, border = 0xffffff, shaft = 0x123456 }
+        elevator = 0
+        levels = [{ file = "missing.tmx" }, { file = "no_boss.tmx", boss = "Henry" }]
+    "#;
+    let level = crate::assets::level::test_level("");
+    let result = load_tower(|name| match name {
+        MANIFEST_FILE => Some(manifest.to_string()),
+        "no_boss.tmx" => Some(level.clone()),
+        _ => None,
+    });
+    let Err(errors) = result else {
+        panic!("expected errors");
+    };
+    assert!(matches!(
+        errors.as_slice(),
+        [
+            LevelLoadError::MissingFile { .. },
+            LevelLoadError::MissingBossTile { .. }
+        ]
+    ));
+
+    let result = load_tower(|_| Some("worlds = 3".to_string()));
+    assert!(matches!(
+        result.err().as_deref(),
+        Some([LevelLoadError::BadManifest { .. }])
+    ));
+
+    let result = load_tower(|_| Some("worlds = []".to_string()));
+    assert!(matches!(
+        result.err().as_deref(),
+        Some([LevelLoadError::NoLevels { .. }])
+    ));
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::assets::{Reloadable, asset};
+
+/// What each tile means, read from the custom properties of `assets/tileset.tsx`
+pub static TILES: Reloadable<TileRegistry> = Reloadable::new("tileset.tsx", || {
+    TileRegistry::parse(&String::from_utf8_lossy(&asset!("tileset.tsx")))
+});
+
+/// What a tile does when placed in a level. Besides [TileKind::Fog], these only apply to the special layer.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum TileKind {
+    #[default]
+    None,
+    LassoTarget,
+    /// Spawns an enemy of the type named by the tile's `enemy` property
+    Enemy,
+    Horse,
+    /// Sets the direction of an adjacent horse. Flips the horse if true.
+    HorseArrow(bool),
+    /// Stops a running horse
+    HorseStop,
+    /// Keeps horses from returning home while the player stands on it
+    HorseWait,
+    /// Spawns the boss with the contained index
+    Boss(usize),
+    PlayerSpawn,
+    LevelEnd,
+    ElevatorShaft,
+    /// Sets the camera offset to the value of the adjacent number tiles
+    CameraOffset,
+    /// Resets the camera offset
+    CameraReset,
+    /// Marks a position for bosses to use, named by the contained index
+    Marker(usize),
+    /// Adds its value to adjacent number tiles
+    Number(u16),
+    /// Halves the sum of adjacent number tiles, the contained number of times
+    Half(i32),
+    EnemyPath,
+    /// Spawns adjacent enemies when the player gets close
+    Spawner,
+    /// Spawns adjacent enemies when the player touches a trigger with the contained ID
+    TriggerSpawner(u8),
+    Trigger(u8),
+    /// A wall the player can climb, copied to the collision layer
+    Climbable,
+    /// Part of a moving platform, which follows the enemy path next to it.
+    /// Adjacent tiles in a row make up one platform.
+    MovingPlatform,
+    /// Pushes bodies inside it sideways, to the left if true
+    Wind(bool),
+    /// Slows bodies inside it and lets them sink slowly.
+    /// The player dies with the tile's death animation once their head is under.
+    Quicksand,
+    /// Removed from the level and replaced by fog. Applies to every layer.
+    Fog,
+}
+
+/// How a tile on the collision layer affects bodies standing on it
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Surface {
+    #[default]
+    Normal,
+    /// Slippery, so bodies on it speed up and slow down slowly
+    Ice,
+    /// Carries bodies standing on it sideways, to the left if true
+    Conveyor(bool),
+    /// Launches bodies that land on it upwards
+    Spring,
+}
+
+/// The solid part of a tile on the collision layer
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum CollisionShape {
+    #[default]
+    Full,
+    TopHalf,
+    BottomHalf,
+    /// 45° slope going up to the right
+    SlopeUpRight,
+    /// 45° slope going up to the left
+    SlopeUpLeft,
+    /// Lower tile of a 22.5° slope going up to the right, which spans two tiles
+    ShallowSlopeUpRightLow,
+    /// Upper tile of a 22.5° slope going up to the right
+    ShallowSlopeUpRightHigh,
+    ShallowSlopeUpLeftLow,
+    ShallowSlopeUpLeftHigh,
+    /// Only solid when landed on from above, and can be dropped through by holding down and jumping
+    Platform,
+}
+impl CollisionShape {
+    /// Gets the top and bottom of the solid part of the tile, `x` pixels from its left edge.
+    /// Both are in pixels from the top of the tile.
+    pub fn span(self, x: f32) -> (f32, f32) {
+        let x = x.clamp(0.0, 8.0);
+        match self {
+            CollisionShape::Full => (0.0, 8.0),
+            CollisionShape::TopHalf => (0.0, 4.0),
+            CollisionShape::BottomHalf => (4.0, 8.0),
+            CollisionShape::SlopeUpRight => (8.0 - x, 8.0),
+            CollisionShape::SlopeUpLeft => (x, 8.0),
+            CollisionShape::ShallowSlopeUpRightLow => (8.0 - x / 2.0, 8.0),
+            CollisionShape::ShallowSlopeUpRightHigh => (4.0 - x / 2.0, 8.0),
+            CollisionShape::ShallowSlopeUpLeftLow => (4.0 + x / 2.0, 8.0),
+            CollisionShape::ShallowSlopeUpLeftHigh => (x / 2.0, 8.0),
+            CollisionShape::Platform => (0.0, 0.0),
+        }
+    }
+    /// Whether a point, relative to the tile's top left corner, is inside the solid part of the tile
+    pub fn contains(self, point: Vec2) -> bool {
+        let (top, bottom) = self.span(point.x);
+        point.y >= top && point.y < bottom
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct TileProperties {
+    pub kind: TileKind,
+    /// The name of the enemy type an enemy tile spawns, as declared in `assets/enemies.toml`
+    pub enemy: Option<String>,
+    /// The solid part of the tile, when placed on the collision layer
+    pub collision: CollisionShape,
+    /// How the tile affects bodies standing on it, when placed on the collision layer
+    pub surface: Surface,
+    /// Whether the tile kills the player, instead of being solid. Only applies to the collision layer.
+    pub deadly: bool,
+    /// Whether the tile is destroyed by explosions. Only applies to the collision layer.
+    pub breakable: bool,
+    /// The player's death animation when killed by the tile
+    pub death_animation: usize,
+    /// Whether the special tile is solid during boss battles
+    pub solid: bool,
+    pub blocks_projectiles: bool,
+    /// The tile's animation, drawn over it while playing
+    pub animation: Option<TileAnimation>,
+}
+
+/// An animation made in Tiled's tile animation editor, out of other tiles in the tileset
+#[derive(Clone, Debug)]
+pub struct TileAnimation {
+    /// The ID of each frame's tile, along with how long it's shown in milliseconds
+    pub frames: Vec<(u16, u32)>,
+    pub total_length: u32,
+    /// How many tiles tall each frame is, going down from the frame's tile
+    pub height: u16,
+}
+impl TileAnimation {
+    /// Gets the ID of the frame's tile at a point in time, in milliseconds
+    pub fn get_at_time(&self, mut time: u32) -> u16 {
+        time %= self.total_length;
+        for (tile, length) in self.frames.iter() {
+            if time >= *length {
+                time -= length;
+            } else {
+                return *tile;
+            }
+        }
+        panic!()
+    }
+}
+
+const EMPTY: TileProperties = TileProperties {
+    kind: TileKind::None,
+    enemy: None,
+    collision: CollisionShape::Full,
+    surface: Surface::Normal,
+    deadly: false,
+    breakable: false,
+    death_animation: 0,
+    solid: false,
+    blocks_projectiles: false,
+    animation: None,
+};
+
+pub struct TileRegistry {
+    /// Properties of every tile that has any, keyed by tile ID
+    tiles: HashMap<u16, TileProperties>,
+}
+impl TileRegistry {
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let document = roxmltree::Document::parse(data).map_err(|f| f.to_string())?;
+        let tile_count: u16 = document
+            .root_element()
+            .attribute("tilecount")
+            .and_then(|f| f.parse().ok())
+            .unwrap_or(u16::MAX);
+        let mut tiles = HashMap::new();
+        for tile in document
+            .root_element()
+            .children()
+            .filter(|f| f.has_tag_name("tile"))
+        {
+            let id: u16 = tile
+                .attribute("id")
+                .and_then(|f| f.parse().ok())
+                .ok_or("tile has an invalid id")?;
+            let mut properties = TileProperties::default();
+            let mut kind = None;
+            let mut value = 0;
+            let mut animation_height = None;
+            for property in tile
+                .children()
+                .filter(|f| f.has_tag_name("properties"))
+                .flat_map(|f| f.children().filter(|f| f.has_tag_name("property")))
+            {
+                let name = property.attribute("name").unwrap_or_default();
+                let text = property.attribute("value").unwrap_or_default();
+                let invalid = || format!("tile {id} has invalid {name} {text:?}");
+                match name {
+                    "kind" => kind = Some(text),
+                    "enemy" => properties.enemy = Some(text.to_string()),
+                    "collision" => {
+                        properties.collision = parse_collision(text).ok_or_else(invalid)?
+                    }
+                    "surface" => properties.surface = parse_surface(text).ok_or_else(invalid)?,
+                    "value" => value = text.parse().map_err(|_| invalid())?,
+                    "deadly" => properties.deadly = text.parse().map_err(|_| invalid())?,
+                    "breakable" => properties.breakable = text.parse().map_err(|_| invalid())?,
+                    "death_animation" => {
+                        properties.death_animation = text.parse().map_err(|_| invalid())?
+                    }
+                    "solid" => properties.solid = text.parse().map_err(|_| invalid())?,
+                    "blocks_projectiles" => {
+                        properties.blocks_projectiles = text.parse().map_err(|_| invalid())?
+                    }
+                    "animation_height" => {
+                        animation_height = Some(text.parse().map_err(|_| invalid())?)
+                    }
+                    _ => return Err(format!("tile {id} has unknown property {name:?}")),
+                }
+            }
+            if let Some(kind) = kind {
+                properties.kind = parse_kind(kind, value)
+                    .ok_or_else(|| format!("tile {id} has unknown kind {kind:?}"))?;
+            }
+            if let Some(animation) = tile.children().find(|f| f.has_tag_name("animation")) {
+                let frames = animation
+                    .children()
+                    .filter(|f| f.has_tag_name("frame"))
+                    .map(|frame| {
+                        let tile = frame.attribute("tileid")?.parse().ok()?;
+                        let duration = frame.attribute("duration")?.parse().ok()?;
+                        (tile < tile_count && duration > 0).then_some((tile, duration))
+                    })
+                    .collect::<Option<Vec<(u16, u32)>>>()
+                    .filter(|f| !f.is_empty())
+                    .ok_or_else(|| format!("tile {id} has an invalid animation"))?;
+                properties.animation = Some(TileAnimation {
+                    total_length: frames.iter().map(|f| f.1).sum(),
+                    frames,
+                    height: animation_height.unwrap_or(1),
+                });
+            } else if animation_height.is_some() {
+                return Err(format!(
+                    "tile {id} has an animation_height but no animation"
+                ));
+            }
+            tiles.insert(id, properties);
+        }
+        Ok(Self { tiles })
+    }
+    /// Gets the properties of a tile as stored in a level's layer data, i.e. 0 for no tile and the tile ID + 1 otherwise
+    pub fn get(&self, tile: u16) -> &TileProperties {
+        tile.checked_sub(1)
+            .and_then(|id| self.tiles.get(&id))
+            .unwrap_or(&EMPTY)
+    }
+}
+
+fn parse_collision(collision: &str) -> Option<CollisionShape> {
+    Some(match collision {
+        "full" => CollisionShape::Full,
+        "top_half" => CollisionShape::TopHalf,
+        "bottom_half" => CollisionShape::BottomHalf,
+        "slope_up_right" => CollisionShape::SlopeUpRight,
+        "slope_up_left" => CollisionShape::SlopeUpLeft,
+        "shallow_slope_up_right_low" => CollisionShape::ShallowSlopeUpRightLow,
+        "shallow_slope_up_right_high" => CollisionShape::ShallowSlopeUpRightHigh,
+        "shallow_slope_up_left_low" => CollisionShape::ShallowSlopeUpLeftLow,
+        "shallow_slope_up_left_high" => CollisionShape::ShallowSlopeUpLeftHigh,
+        "platform" => CollisionShape::Platform,
+        _ => return None,
+    })
+}
+
+fn parse_surface(surface: &str) -> Option<Surface> {
+    Some(match surface {
+        "normal" => Surface::Normal,
+        "ice" => Surface::Ice,
+        "conveyor_left" => Surface::Conveyor(true),
+        "conveyor_right" => Surface::Conveyor(false),
+        "spring" => Surface::Spring,
+        _ => return None,
+    })
+}
+
+fn parse_kind(kind: &str, value: u16) -> Option<TileKind> {
+    Some(match kind {
+        "lasso_target" => TileKind::LassoTarget,
+        "enemy" => TileKind::Enemy,
+        "horse" => TileKind::Horse,
+        "horse_arrow" => TileKind::HorseArrow(value != 0),
+        "horse_stop" => TileKind::HorseStop,
+        "horse_wait" => TileKind::HorseWait,
+        "boss" => TileKind::Boss(value as usize),
+        "player_spawn" => TileKind::PlayerSpawn,
+        "level_end" => TileKind::LevelEnd,
+        "elevator_shaft" => TileKind::ElevatorShaft,
+        "camera_offset" => TileKind::CameraOffset,
+        "camera_reset" => TileKind::CameraReset,
+        "marker" => TileKind::Marker(value as usize),
+        "number" => TileKind::Number(value),
+        "half" => TileKind::Half(value as i32),
+        "enemy_path" => TileKind::EnemyPath,
+        "spawner" => TileKind::Spawner,
+        "trigger_spawner" => TileKind::TriggerSpawner(value as u8),
+        "trigger" => TileKind::Trigger(value as u8),
+        "climbable" => TileKind::Climbable,
+        "moving_platform" => TileKind::MovingPlatform,
+        "wind" => TileKind::Wind(value != 0),
+        "quicksand" => TileKind::Quicksand,
+        "fog" => TileKind::Fog,
+        _ => return None,
+    })
+}
+
+#[test]
+fn shipped_tiles_load() {
+    assert_eq!(TILES.get(0).kind, TileKind::None);
+    assert_eq!(TILES.get(1).kind, TileKind::LassoTarget);
+    assert_eq!(TILES.get(3 + 1).kind, TileKind::Enemy);
+    assert_eq!(TILES.get(3 + 1).enemy.as_deref(), Some("demo_bandit"));
+    assert_eq!(TILES.get(610 + 1).kind, TileKind::Trigger(2));
+    assert_eq!(TILES.get(995 + 1).kind, TileKind::Number(4));
+    let death_animations: Vec<usize> = [128, 352, 288]
+        .iter()
+        .map(|f| TILES.get(f + 1))
+        .filter(|f| f.deadly)
+        .map(|f| f.death_animation)
+        .collect();
+    assert_eq!(death_animations, [0, 1, 2]);
+    assert!(TILES.get(672 + 1).blocks_projectiles);
+    assert_eq!(TILES.get(1893 + 1).surface, Surface::Conveyor(true));
+    assert_eq!(TILES.get(1898 + 1).kind, TileKind::Wind(true));
+    // shelves placed in shipped levels stay solid, apart from the one-way platform tile
+    assert_eq!(TILES.get(228 + 1).collision, CollisionShape::Full);
+    assert_eq!(TILES.get(1900 + 1).collision, CollisionShape::Platform);
+    assert_eq!(TILES.get(1901 + 1).kind, TileKind::MovingPlatform);
+    let lava = TILES.get(320 + 1).animation.as_ref().unwrap();
+    assert_eq!(
+        (lava.frames.len(), lava.total_length, lava.height),
+        (8, 3000, 2)
+    );
+    assert_eq!(lava.get_at_time(1250), lava.frames[3].0);
+
+    let bad = |properties: &str| {
+        TileRegistry::parse(&format!(
+            "<tileset><tile id=\"5\"><properties>{properties}</properties></tile></tileset>"
+        ))
+        .err()
+    };
+    assert!(bad("<property name=\"kind\" value=\"dragon\"/>").is_some());
+    assert!(bad("<property name=\"deadly\" type=\"bool\" value=\"yes\"/>").is_some());
+    assert!(bad("<property name=\"collision\" value=\"ramp\"/>").is_some());
+    assert!(bad("<property name=\"surface\" value=\"lava\"/>").is_some());
+    assert!(bad("<property name=\"collision\" value=\"slope_up_left\"/>").is_none());
+    assert!(bad("<property name=\"kind\" value=\"marker\"/>").is_none());
+    assert!(bad("<property name=\"animation_height\" type=\"int\" value=\"2\"/>").is_some());
+    let animation = |frames: &str| {
+        TileRegistry::parse(&format!(
+            "<tileset tilecount=\"64\"><tile id=\"5\"><animation>{frames}</animation></tile></tileset>"
+        ))
+        .err()
+    };
+    assert!(animation("<frame tileid=\"6\" duration=\"100\"/>").is_none());
+    assert!(animation("<frame tileid=\"64\" duration=\"100\"/>").is_some());
+    assert!(animation("").is_some());
+}
@@ -0,0 +1,67 @@
+//! Checks every level in `levels/` for problems, without opening a window.
+//!
+//! Exits with a non-zero code if any level fails to load or has warnings, so it can be run before committing.
+//! Reads from disk, so doesn't need rebuilding after editing a level.
+
+use std::{collections::HashSet, fs, path::Path, process::ExitCode};
+
+use cowboy_tower::assets::{LEVELS_PATH, LevelData, MANIFEST_FILE, load_tower};
+
+fn read_level_file(name: &str) -> Option<String> {
+    fs::read_to_string(Path::new(LEVELS_PATH).join(name)).ok()
+}
+
+fn main() -> ExitCode {
+    let (_, levels) = match load_tower(read_level_file) {
+        Ok(tower) => tower,
+        Err(errors) => {
+            for error in errors.iter() {
+                println!("error: {error}");
+            }
+            println!("{} error(s), fix these before linting", errors.len());
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut problems = 0;
+    for level in levels.iter() {
+        for warning in level.lint() {
+            println!("warning: {}: {warning}", level.name);
+            problems += 1;
+        }
+    }
+
+    // levels that aren't in the manifest aren't played, but should still be valid
+    let listed: HashSet<&str> = levels.iter().map(|f| f.name.as_str()).collect();
+    let mut unlisted: Vec<String> = fs::read_dir(LEVELS_PATH)
+        .map(|f| {
+            f.filter_map(|f| Some(f.ok()?.file_name().to_str()?.to_string()))
+                .filter(|f| f.ends_with(".tmx") && !listed.contains(f.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    unlisted.sort();
+    for name in unlisted {
+        println!("warning: {name}: not listed in {MANIFEST_FILE}");
+        problems += 1;
+        let data = read_level_file(&name).unwrap_or_default();
+        match LevelData::parse(&data, name.clone()) {
+            Ok(level) => {
+                for warning in level.lint() {
+                    println!("warning: {name}: {warning}");
+                    problems += 1;
+                }
+            }
+            Err(error) => {
+                println!("error: {error}");
+                problems += 1;
+            }
+        }
+    }
+
+    println!("checked {} level(s), {problems} problem(s)", levels.len());
+    if problems > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -4,7 +4,7 @@ mod fireking;
 mod henry;
 
 use crate::{
-    assets::{Assets, Level},
+    assets::{Assets, Level, LevelData},
     bosses::fireking::Fireking,
     bosses::henry::Henry,
     player::Player,
@@ -24,10 +24,24 @@ pub trait Boss {
     }
 }
 
-pub fn new_boss(index: usize, pos: Vec2) -> Box<dyn Boss> {
+/// The name of each boss and the markers it needs in its level, indexed like [new_boss]
+pub const BOSS_MARKERS: [(&str, &[&str]); 2] = [
+    ("Henry", henry::REQUIRED_MARKERS),
+    ("Fireking", fireking::REQUIRED_MARKERS),
+];
+
+/// Creates a boss. Its level must have the boss' markers, which [LevelData::parse] makes sure of.
+pub fn new_boss(index: usize, pos: Vec2, level: &LevelData) -> Box<dyn Boss> {
     match index {
-        0 => Box::new(Henry::new(pos)),
-        1 => Box::new(Fireking::new(pos)),
+        0 => Box::new(Henry::new(pos, level)),
+        1 => Box::new(Fireking::new(pos, level)),
         _ => panic!(),
     }
 }
+
+/// Gets a marker listed in a boss' required markers
+fn required_marker(level: &LevelData, name: &str) -> Vec2 {
+    level
+        .find_marker(name)
+        .expect("required markers are checked when the level is loaded")
+}
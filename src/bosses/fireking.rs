@@ -1,13 +1,27 @@
 use macroquad::prelude::*;
 
 use crate::{
-    assets::{Assets, Level},
-    bosses::Boss,
+    assets::{Assets, Level, LevelData},
+    bosses::{Boss, required_marker},
     player::Player,
     projectiles::Projectile,
     utils::DEBUG_FLAGS,
 };
 
+/// Left and right edges of the arena, where fireballs can land
+const LEFT_TARGET_MARKER: &str = "0";
+const RIGHT_TARGET_MARKER: &str = "1";
+/// The lavafall, past which the player dies
+const LAVAFALL_MARKER: &str = "3";
+/// Where the player has to walk to start the dialogue
+const DIALOGUE_MARKER: &str = "4";
+pub const REQUIRED_MARKERS: &[&str] = &[
+    LEFT_TARGET_MARKER,
+    RIGHT_TARGET_MARKER,
+    LAVAFALL_MARKER,
+    DIALOGUE_MARKER,
+];
+
 fn populate_fireball_positions(
     positions: &mut [f32],
     player_pos: Vec2,
@@ -52,16 +66,24 @@ enum State {
 pub struct Fireking {
     pos: Vec2,
     spawn: Vec2,
-    health: u8,
+    health: u32,
     state: State,
     time: f32,
     activated: f32,
     blood_effects: Vec<(Vec2, f32, bool)>,
     dialogue_id: usize,
+    left_target: Vec2,
+    right_target: Vec2,
+    lavafall: Vec2,
+    dialogue_marker: Vec2,
 }
 impl Fireking {
-    pub fn new(pos: Vec2) -> Self {
+    pub fn new(pos: Vec2, level: &LevelData) -> Self {
         Fireking {
+            left_target: required_marker(level, LEFT_TARGET_MARKER),
+            right_target: required_marker(level, RIGHT_TARGET_MARKER),
+            lavafall: required_marker(level, LAVAFALL_MARKER),
+            dialogue_marker: required_marker(level, DIALOGUE_MARKER),
             pos,
             spawn: pos,
             health: 10,
@@ -96,8 +118,8 @@ impl Boss for Fireking {
 
         let dead = matches!(self.state, State::Death(_));
 
-        let left_target = level.find_marker(0);
-        let right_target = level.find_marker(1);
+        let left_target = self.left_target;
+        let right_target = self.right_target;
 
         if player.pos.x <= right_target.x {
             player.camera_pos.y = player.camera_pos.y.min(-80.0);
@@ -122,11 +144,11 @@ impl Boss for Fireking {
                         player.show_dialogue(dialogue_messages[self.dialogue_id], "Fireking", 1);
                     }
                 }
-            } else if player.pos.x < level.find_marker(4).x && player.on_ground {
+            } else if player.pos.x < self.dialogue_marker.x && player.on_ground {
                 player.show_dialogue(dialogue_messages[0], "Fireking", 1);
             }
         } else {
-            if player.death.is_none() && player.pos.x > level.find_marker(3).x + 8.0 {
+            if player.death.is_none() && player.pos.x > self.lavafall.x + 8.0 {
                 player.death = Some((0.0, 1, false))
             }
 
@@ -318,7 +340,7 @@ impl Boss for Fireking {
                 && (self.pos.x - 8.0..self.pos.x + 8.0).contains(&projectile.pos.x)
             {
                 projectile.dead = true;
-                self.health = self.health.saturating_sub(1);
+                self.health = self.health.saturating_sub(projectile.damage);
                 self.blood_effects
                     .push((projectile.pos, 0.0, projectile.direction.x > 0.0));
             }
@@ -368,7 +390,7 @@ impl Boss for Fireking {
         }
 
         if self.activated > 0.0 {
-            let lavafall_pos = level.find_marker(3);
+            let lavafall_pos = self.lavafall;
             draw_texture(
                 assets.lavafall.get_at_time((self.time * 1000.0) as u32),
                 lavafall_pos.x + 2.0,
@@ -1,13 +1,28 @@
 use macroquad::prelude::*;
 
 use crate::{
-    assets::{Assets, Level},
-    bosses::Boss,
+    assets::{Assets, Level, LevelData},
+    bosses::{Boss, required_marker},
     player::Player,
     projectiles::Projectile,
     utils::DEBUG_FLAGS,
 };
 
+/// Where Henry jumps to on the left and right side of the arena
+const LEFT_MARKER: &str = "0";
+const RIGHT_MARKER: &str = "1";
+/// Where the poles are raised once Henry is defeated
+const POLE_MARKERS: [&str; 2] = ["2", "3"];
+/// Where the player has to walk to start the dialogue
+const DIALOGUE_MARKER: &str = "4";
+pub const REQUIRED_MARKERS: &[&str] = &[
+    LEFT_MARKER,
+    RIGHT_MARKER,
+    POLE_MARKERS[0],
+    POLE_MARKERS[1],
+    DIALOGUE_MARKER,
+];
+
 enum State {
     Idle,
     /// - amount of jumps completed,
@@ -22,16 +37,24 @@ enum State {
 pub struct Henry {
     pos: Vec2,
     spawn: Vec2,
-    health: u8,
+    health: u32,
     state: State,
     time: f32,
     blood_effects: Vec<(Vec2, f32, bool)>,
     dust_particles: Vec<(Vec2, f32)>,
     activated: f32,
+    left_marker: Vec2,
+    right_marker: Vec2,
+    poles: [Vec2; 2],
+    dialogue_marker: Vec2,
 }
 impl Henry {
-    pub fn new(pos: Vec2) -> Self {
+    pub fn new(pos: Vec2, level: &LevelData) -> Self {
         Henry {
+            left_marker: required_marker(level, LEFT_MARKER),
+            right_marker: required_marker(level, RIGHT_MARKER),
+            poles: POLE_MARKERS.map(|f| required_marker(level, f)),
+            dialogue_marker: required_marker(level, DIALOGUE_MARKER),
             pos,
             spawn: pos,
             health: 12,
@@ -48,7 +71,7 @@ impl Boss for Henry {
         &mut self,
         assets: &Assets,
         delta_time: f32,
-        level: &Level,
+        _level: &Level,
         projectiles: &mut Vec<Projectile>,
         player: &mut Player,
     ) {
@@ -66,7 +89,7 @@ impl Boss for Henry {
                 player.active_dialogue = None;
                 player.in_boss_battle = true;
             }
-        } else if player.pos.x < level.find_marker(4).x {
+        } else if player.pos.x < self.dialogue_marker.x {
             player.show_dialogue(
                 "Hm. A puny little cowboy.\nYou will be crushed.",
                 "Henry the Cooper",
@@ -92,7 +115,7 @@ impl Boss for Henry {
         }
 
         if let Some(time) = pole_anim_time {
-            for pos in [level.find_marker(2), level.find_marker(3)] {
+            for pos in self.poles {
                 let t = pole_anim.get_at_time((time * 1000.0) as u32);
                 draw_texture(t, pos.x, pos.y - t.height() + 4.0, WHITE);
             }
@@ -141,8 +164,8 @@ impl Boss for Henry {
                     dest.x = player.pos.x;
                     self.dust_particles.push((self.pos, 0.0));
                     if *amt >= JUMP_AMT - 1 {
-                        let left_marker = level.find_marker(0);
-                        let right_marker = level.find_marker(1);
+                        let left_marker = self.left_marker;
+                        let right_marker = self.right_marker;
                         if *amt == JUMP_AMT - 1 {
                             let left_side = (player.pos.x - left_marker.x).abs()
                                 > (player.pos.x - right_marker.x).abs();
@@ -208,7 +231,7 @@ impl Boss for Henry {
             for projectile in projectiles {
                 if projectile.friendly && (self.pos - 8.0).distance(projectile.pos) <= 18.0 {
                     projectile.dead = true;
-                    self.health = self.health.saturating_sub(1);
+                    self.health = self.health.saturating_sub(projectile.damage);
                     self.blood_effects.push((
                         projectile.pos.move_towards(self.pos, 4.0),
                         0.0,
@@ -1,11 +1,34 @@
 use crate::{
-    assets::{AnimationsGroup, Assets, Level},
-    player::{Player, update_physicsbody},
+    assets::{
+        AnimationsGroup, Assets, Level, Reloadable, Surface, TILES, TileKind, asset,
+        read_named_asset, tile_of,
+    },
+    player::{
+        ICE_ACCELERATION, Player, RayFilter, bounce_on_spring, raycast, surface_at,
+        update_physicsbody,
+    },
     projectiles::Projectile,
-    utils::{DEBUG_FLAGS, draw_cross},
+    utils::{DEBUG_FLAGS, FLASH_MATERIAL, GRAVITY, draw_cross},
 };
 use macroquad::prelude::*;
-use std::{f32::consts::PI, sync::LazyLock};
+use serde::Deserialize;
+use std::{any::Any, f32::consts::PI, sync::Arc};
+
+pub use attack::*;
+pub use movement::*;
+
+mod attack;
+mod movement;
+
+/// How long enemies flash white after being hit, in seconds
+const HIT_FLASH_TIME: f32 = 0.1;
+/// How long enemies can't be hit again after being hit, in seconds
+const INVULNERABILITY_TIME: f32 = 0.4;
+/// Speed enemies are knocked back with when hit, away from the projectile.
+/// Only sideways, since most enemies aren't pulled down by gravity.
+const KNOCKBACK_SPEED: f32 = 64.0;
+/// How fast knocked back enemies slide to a stop, in pixels per second squared
+const KNOCKBACK_FRICTION: f32 = 160.0;
 
 #[derive(Clone, Copy)]
 pub enum EnemySpawner {
@@ -25,16 +48,94 @@ pub struct Enemy {
     pub ty: &'static EnemyType,
     pub path_index: Option<(usize, usize)>,
     pub time: f32,
-    /// Used for attack type ShootAfter
-    pub has_attacked: bool,
+    /// How many times the enemy has hit or shot at the player during its current attack
+    pub attacks: u32,
     pub attack_time: f32,
     /// Set to zero when alive. On death, tracks death animation time
     pub death_frames: f32,
+    /// Damage left to take before the enemy dies
+    pub health: u32,
+    /// Seconds left until the enemy can be hit again. Knocked back enemies don't move on their own until it's over.
+    pub invulnerable: f32,
+    /// Whether the enemy was standing on something after it last moved
+    pub grounded: bool,
+    /// What the enemy's movement keeps track of, made by [Movement::new_state]
+    pub state: Box<dyn Any>,
     /// Random seed for each enemy, used for random-esque movement and behaviour
     pub wibble_wobble: f32,
     pub spawner: Option<EnemySpawner>,
 }
 impl Enemy {
+    /// Whether there's nothing solid between the enemy and the player
+    fn can_see(&self, player: &Player, level: &Level) -> bool {
+        raycast(self.pos + 4.0, player.pos + 4.0, level, RayFilter::Solid).is_none()
+    }
+    /// Fires a projectile towards the player. Without aiming, it goes straight to the side the player is on.
+    fn shoot(&self, sprite: usize, aim: bool, player: &Player, projectiles: &mut Vec<Projectile>) {
+        let side = vec2(if self.pos.x > player.pos.x { -1.0 } else { 1.0 }, 0.0);
+        if !Projectile::shoot_offset(sprite) {
+            projectiles.push(Projectile::new(sprite, self.pos, side));
+            return;
+        }
+        let muzzle = self.pos + vec2(4.0, 0.0);
+        let direction = if aim {
+            (player.pos + 4.0 - muzzle).normalize_or(side)
+        } else {
+            side
+        };
+        projectiles.push(Projectile::new(sprite, muzzle + direction * 8.0, direction));
+    }
+    /// Flies towards the player, around anything solid in the way. The way there is only found again once the
+    /// enemy or the player moves to another tile. Without a way there, flies straight at the player.
+    fn fly(&mut self, flying_path: &mut FlyingPath, player: &Player, level: &Level) {
+        let ends = (tile_of(self.pos), tile_of(player.pos));
+        if flying_path.ends != Some(ends) {
+            flying_path.ends = Some(ends);
+            flying_path.path = level.find_flying_path(ends.0, ends.1);
+        }
+        let target = flying_path
+            .path
+            .as_ref()
+            .and_then(|f| f.get(1))
+            .map_or(player.pos, |f| vec2(f.0 as f32, f.1 as f32) * 8.0);
+        self.velocity = (target - self.pos).normalize_or_zero() * self.ty.speed * 8.0;
+    }
+    fn walk_towards(&mut self, x: f32, level: &Level, delta_time: f32) {
+        let distance = x - self.pos.x;
+        let direction = if distance.abs() < 1.0 {
+            0.0
+        } else {
+            distance.signum()
+        };
+        self.walk(direction * self.ty.speed, level, delta_time);
+    }
+    /// Sets the enemy's walking speed, which it only gets up to slowly on ice
+    fn walk(&mut self, speed: f32, level: &Level, delta_time: f32) {
+        self.velocity.x = if surface_at(self.pos, level) == Surface::Ice {
+            self.velocity.x.lerp(speed, delta_time * ICE_ACCELERATION)
+        } else {
+            speed
+        };
+    }
+    /// Takes damage from the first friendly projectile inside the enemy's hitbox, unless it was hit too recently
+    fn take_hits(&mut self, projectiles: &mut [Projectile]) {
+        if self.invulnerable > 0.0 {
+            return;
+        }
+        let hitbox = self.ty.hitbox.offset(self.pos);
+        let Some(projectile) = projectiles
+            .iter_mut()
+            .find(|f| f.friendly && f.can_kill() && hitbox.contains(f.pos))
+        else {
+            return;
+        };
+        projectile.dead |= projectile.should_die_on_kill();
+        self.health = self.health.saturating_sub(projectile.damage);
+        self.invulnerable = INVULNERABILITY_TIME;
+        if self.ty.movement.can_be_knocked_back(self) {
+            self.velocity.x = KNOCKBACK_SPEED * projectile.direction.x.signum();
+        }
+    }
     fn trigger_spawning(&mut self) {
         if self.ty.animation.tag_names.contains_key("spawning") {
             self.spawner = Some(EnemySpawner::Spawning(0.0));
@@ -42,24 +143,26 @@ impl Enemy {
             self.spawner = None;
         }
     }
-    pub fn update(
+    /// Spawns, moves and attacks with the enemy, without drawing it
+    fn think(
         &mut self,
         player: &mut Player,
         projectiles: &mut Vec<Projectile>,
-        assets: &Assets,
         level: &Level,
         delta_time: f32,
-    ) -> bool {
+    ) {
         self.time += delta_time;
+        self.invulnerable = (self.invulnerable - delta_time).max(0.0);
 
         let player_tx = (player.pos / 8.0).floor();
         let player_tile = level.get_tile(player_tx.x as i16, player_tx.y as i16);
 
-        let mut force_moving_animation = false;
         if self.death_frames > 0.0 {
             self.death_frames += delta_time;
             self.time = 0.0;
-        } else if let Some(spawner) = &mut self.spawner {
+            return;
+        }
+        if let Some(spawner) = &mut self.spawner {
             match spawner {
                 EnemySpawner::Spawning(time) => {
                     *time += delta_time;
@@ -77,125 +180,76 @@ impl Enemy {
                     }
                 }
                 EnemySpawner::Trigger(id) => {
-                    if player_tile[3] > 608
-                        && player_tile[3] <= 612 + 1
-                        && (player_tile[3] - (608 + 1)) as u8 == *id
+                    if matches!(TILES.get(player_tile[3]).kind, TileKind::Trigger(trigger) if trigger == *id)
+                        || level
+                            .trigger_zones
+                            .iter()
+                            .any(|f| f.1 == *id && f.0.contains(player.pos))
                     {
                         self.trigger_spawning();
                     }
                 }
             }
+            return;
+        }
+        let ty = self.ty;
+        if ty.movement.has_gravity() {
+            self.velocity.y += GRAVITY * delta_time;
+        }
+        if self.invulnerable > 0.0 && ty.movement.can_be_knocked_back(self) {
+            let friction = (KNOCKBACK_FRICTION * delta_time).min(self.velocity.x.abs());
+            self.velocity.x -= friction * self.velocity.x.signum();
         } else {
-            match self.ty.movement_type {
-                MovementType::None => {}
-                MovementType::FollowPath => {
-                    force_moving_animation = true;
-                    let (path_index, path_tile_index) = self.path_index.unwrap();
-                    let path = &level.enemy_paths[path_index];
-                    let time_per_tile = 1.0 / self.ty.speed;
-                    let path_time = path.len() as f32 * time_per_tile;
-                    let value = (self.time + path_tile_index as f32 * time_per_tile) % path_time
-                        / time_per_tile;
-                    let value_index = value.floor();
-
-                    let current = path[value_index as usize];
-                    let next = path[(value_index as usize + 1) % path.len()];
-                    let amt_between = value - value_index;
-                    self.pos = current.lerp(next, amt_between);
-                }
-                MovementType::Wander => {
-                    let value = self.time + self.wibble_wobble;
-                    // values for this formula found with `find_lowest_drift_factor`
-                    let value = value.sin()
-                        * (value * 4.627175 + 1.5).sin()
-                        * (value * 5.306475 + 8.0).sin().powi(2);
-                    let value = if value.abs() < 0.1 {
-                        0.0
-                    } else if value.is_sign_positive() {
-                        1.0
-                    } else {
-                        -1.0
-                    };
-                    self.velocity.x = value * self.ty.speed;
-                }
-                MovementType::Chase => {
-                    let mut direction = self.pos - player.pos;
-                    direction.y = 0.0;
-                    if direction.x.abs() < 1.0 {
-                        direction.x = 0.0;
-                    }
-                    let move_dir = -direction.normalize_or_zero().x;
-                    self.velocity.x = move_dir * self.ty.speed;
-                }
-            }
-            if self.attack_time <= 0.0 {
-                if player.death.is_none() {
-                    self.attack_time += delta_time;
-                    match self.ty.attack_type {
-                        AttackType::None => {
-                            self.attack_time = 0.0;
-                        }
-                        AttackType::Melee => {
-                            self.attack_time = 0.0;
-                            if (player.pos + 4.0).distance(self.pos + 4.0) < 5.0 {
-                                player.death = Some((0.0, 0, true))
-                            }
-                        }
-                        AttackType::ShootAfter(_) => {}
-                        AttackType::Shoot(sprite) => {
-                            let pos = if Projectile::shoot_offset(sprite) {
-                                self.pos
-                                    + if self.pos.x > player.pos.x {
-                                        vec2(-8.0, 0.0)
-                                    } else {
-                                        vec2(8.0, 0.0)
-                                    }
-                                    + vec2(4.0, 0.0)
-                            } else {
-                                self.pos
-                            };
-                            projectiles.push(Projectile::new(
-                                sprite,
-                                pos,
-                                vec2(if self.pos.x > player.pos.x { -1.0 } else { 1.0 }, 0.0),
-                            ));
-                        }
-                    }
-                }
-            } else {
+            // the movement gets the enemy while its state is taken out of it
+            let mut state = std::mem::replace(&mut self.state, Box::new(()));
+            ty.movement
+                .update(self, state.as_mut(), player, level, delta_time);
+            self.state = state;
+        }
+        if self.attack_time <= 0.0 {
+            if player.death.is_none()
+                && self.pos.distance(player.pos) <= ty.range
+                && (!ty.attack.needs_sight() || self.can_see(player, level))
+            {
                 self.attack_time += delta_time;
-                let delta = self.attack_time * 1000.0
-                    - self.ty.animation.get_by_name("attack").total_length as f32;
-                if delta >= 0.0
-                    && !self.has_attacked
-                    && let AttackType::ShootAfter(sprite) = self.ty.attack_type
-                {
-                    let pos = if Projectile::shoot_offset(sprite) {
-                        self.pos
-                            + if self.pos.x > player.pos.x {
-                                vec2(-8.0, 0.0)
-                            } else {
-                                vec2(8.0, 0.0)
-                            }
-                            + vec2(4.0, 0.0)
-                    } else {
-                        self.pos
-                    };
-                    projectiles.push(Projectile::new(
-                        sprite,
-                        pos,
-                        vec2(if self.pos.x > player.pos.x { -1.0 } else { 1.0 }, 0.0),
-                    ));
-                    self.has_attacked = true;
-                }
-                if delta >= self.ty.attack_delay * 1000.0 {
+                if !ty.attack.start(self, player, projectiles) {
                     self.attack_time = 0.0;
-                    self.has_attacked = false;
                 }
             }
-            (self.pos, _, _, _) =
-                update_physicsbody(self.pos, &mut self.velocity, delta_time, level, true, false);
+        } else {
+            self.attack_time += delta_time;
+            let time =
+                self.attack_time - ty.animation.get_by_name("attack").total_length as f32 / 1000.0;
+            ty.attack.update(self, player, projectiles, time);
+            if time >= ty.attack.length() + ty.attack_delay {
+                self.attack_time = 0.0;
+                self.attacks = 0;
+            }
+        }
+        let tall = ty.movement.is_tall(self);
+        (self.pos, self.grounded, _, _, _) = update_physicsbody(
+            self.pos,
+            &mut self.velocity,
+            delta_time,
+            level,
+            tall,
+            false,
+            false,
+        );
+        // enemies that float would never come back down
+        if ty.movement.has_gravity() && bounce_on_spring(self.pos, &mut self.velocity, level) {
+            self.grounded = false;
         }
+    }
+    pub fn update(
+        &mut self,
+        player: &mut Player,
+        projectiles: &mut Vec<Projectile>,
+        assets: &Assets,
+        level: &Level,
+        delta_time: f32,
+    ) -> bool {
+        self.think(player, projectiles, level, delta_time);
         let rotation = if self.death_frames <= 0.0 {
             0.0
         } else {
@@ -219,7 +273,7 @@ impl Enemy {
             (self.ty.animation.tag_names["attack"], self.attack_time)
         } else {
             (
-                if force_moving_animation || self.velocity.x.abs() > 5.0 {
+                if self.ty.movement.is_moving(self) {
                     1
                 } else {
                     0
@@ -234,7 +288,11 @@ impl Enemy {
         } else {
             1.0
         };
+        let flash = self.invulnerable > INVULNERABILITY_TIME - HIT_FLASH_TIME;
 
+        if flash {
+            gl_use_material(&FLASH_MATERIAL);
+        }
         draw_texture_ex(
             self.ty.animation.animations[animation_id].get_at_time((time * 1000.0) as u32),
             self.pos.x.floor() - 8.0,
@@ -246,26 +304,16 @@ impl Enemy {
                 ..Default::default()
             },
         );
+        if flash {
+            gl_use_default_material();
+        }
         if DEBUG_FLAGS.centres {
             draw_cross(self.pos.x, self.pos.y, RED);
         }
         if self.death_frames <= 0.0 {
             if self.spawner.is_none() {
-                let mut hit_by_projectile = false;
-                for projectile in projectiles.iter_mut() {
-                    if projectile.friendly
-                        && projectile.can_kill()
-                        && ((projectile.pos.x - 4.0)..(projectile.pos.x + 4.0))
-                            .contains(&(self.pos.x + 4.0))
-                        && ((projectile.pos.y - 8.0)..(projectile.pos.y + 4.0))
-                            .contains(&self.pos.y)
-                    {
-                        projectile.dead |= projectile.should_die_on_kill();
-                        hit_by_projectile = true;
-                        break;
-                    }
-                }
-                if hit_by_projectile {
+                self.take_hits(projectiles);
+                if self.health == 0 {
                     self.death_frames += delta_time;
                 }
             }
@@ -292,79 +340,326 @@ impl Enemy {
 #[derive(Clone, Copy)]
 pub struct LevelEnemyData {
     pub pos: Vec2,
-    pub ty: &'static EnemyType,
+    /// Index into [ENEMIES] and [ENEMY_DEFINITIONS]
+    pub ty: usize,
     pub attack_delay: f32,
     pub path_index: Option<(usize, usize)>,
     pub spawner: Option<EnemySpawner>,
 }
 
-#[allow(dead_code)]
-pub enum MovementType {
-    None,
-    Wander,
-    FollowPath,
-    Chase,
+/// An enemy type as declared in `assets/enemies.toml`, without its sprite loaded
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "RawEnemyDefinition")]
+pub struct EnemyDefinition {
+    /// What enemy tiles call the enemy type
+    pub name: String,
+    /// The `.ase` file in `assets/` the enemy's animations are read from
+    pub sprite: String,
+    pub movement: Arc<dyn Movement>,
+    pub attack: Arc<dyn Attack>,
+    pub attack_delay: f32,
+    pub speed: f32,
+    /// Where projectiles hit the enemy, relative to its position
+    pub hitbox: Rect,
+    /// How many hits it takes to kill the enemy
+    pub health: u32,
+    /// Whether the enemy shoots straight at the player, instead of only to the side
+    pub aim: bool,
+    /// How close the player has to be for the enemy to attack, in pixels
+    pub range: f32,
+}
+
+/// Makes an enemy type's movement or attack from its definition
+type MakeBehaviour<T> = fn(&RawEnemyDefinition) -> Result<Arc<T>, String>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawEnemyDefinition {
+    name: String,
+    sprite: String,
+    movement: String,
+    attack: String,
+    projectile: Option<usize>,
+    #[serde(default)]
+    speed: f32,
+    #[serde(default)]
+    attack_delay: f32,
+    hitbox: Option<[f32; 4]>,
+    health: Option<u32>,
+    #[serde(default)]
+    aim: bool,
+    range: Option<f32>,
+    #[serde(default)]
+    jump_height: u8,
+    max_drop: Option<u8>,
+    shots: Option<u32>,
 }
+impl TryFrom<RawEnemyDefinition> for EnemyDefinition {
+    type Error = String;
 
-#[allow(dead_code)]
-pub enum AttackType {
-    None,
-    Shoot(usize),
-    /// Like shoot, but projectile is fired after animation is completed
-    ShootAfter(usize),
-    Melee,
+    fn try_from(raw: RawEnemyDefinition) -> Result<Self, String> {
+        let movement = MOVEMENTS
+            .iter()
+            .find(|f| f.0 == raw.movement)
+            .ok_or_else(|| {
+                format!(
+                    "enemy {:?} has unknown movement {:?}",
+                    raw.name, raw.movement
+                )
+            })?
+            .1(&raw)?;
+        let attack = ATTACKS
+            .iter()
+            .find(|f| f.0 == raw.attack)
+            .ok_or_else(|| format!("enemy {:?} has unknown attack {:?}", raw.name, raw.attack))?
+            .1(&raw)?;
+        let [x, y, w, h] = raw.hitbox.unwrap_or([0.0, -4.0, 8.0, 12.0]);
+        Ok(Self {
+            movement,
+            attack,
+            attack_delay: raw.attack_delay,
+            speed: raw.speed,
+            hitbox: Rect::new(x, y, w, h),
+            health: raw.health.unwrap_or(1),
+            aim: raw.aim,
+            range: raw.range.unwrap_or(f32::INFINITY),
+            name: raw.name,
+            sprite: raw.sprite,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct EnemyFile {
+    enemies: Vec<EnemyDefinition>,
+}
+
+/// Parses the enemy types in an `enemies.toml` file. `sprite_exists` checks that a sprite file can be loaded.
+pub fn parse_enemy_definitions(
+    text: &str,
+    sprite_exists: impl Fn(&str) -> bool,
+) -> Result<Vec<EnemyDefinition>, String> {
+    let file: EnemyFile = toml::from_str(text).map_err(|f| f.to_string())?;
+    for (i, enemy) in file.enemies.iter().enumerate() {
+        if file.enemies[..i].iter().any(|f| f.name == enemy.name) {
+            return Err(format!("enemy {:?} is declared twice", enemy.name));
+        }
+        if !sprite_exists(&enemy.sprite) {
+            return Err(format!(
+                "enemy {:?} has missing sprite {:?}",
+                enemy.name, enemy.sprite
+            ));
+        }
+    }
+    Ok(file.enemies)
+}
+
+/// Every enemy type, read from `assets/enemies.toml`.
+/// Kept separate from [ENEMIES] so levels can be validated without loading any enemy textures.
+pub static ENEMY_DEFINITIONS: Reloadable<Vec<EnemyDefinition>> =
+    Reloadable::new("enemies.toml", || {
+        parse_enemy_definitions(&String::from_utf8_lossy(&asset!("enemies.toml")), |f| {
+            read_named_asset(f).is_some()
+        })
+    });
+
+/// Finds the index of an enemy type in [ENEMY_DEFINITIONS] and [ENEMIES] by its name
+pub fn find_enemy_type(name: &str) -> Option<usize> {
+    ENEMY_DEFINITIONS.iter().position(|f| f.name == name)
 }
 
 pub struct EnemyType {
     pub animation: AnimationsGroup,
-    pub movement_type: MovementType,
-    pub attack_type: AttackType,
+    pub movement: Arc<dyn Movement>,
+    pub attack: Arc<dyn Attack>,
     pub attack_delay: f32,
     pub speed: f32,
+    pub hitbox: Rect,
+    pub health: u32,
+    pub aim: bool,
+    pub range: f32,
 }
-pub static ENEMIES: LazyLock<Vec<EnemyType>> = LazyLock::new(|| {
-    vec![
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/bandit.ase")),
-            movement_type: MovementType::Wander,
-            speed: 16.0,
-            attack_type: AttackType::Shoot(1),
-            attack_delay: 1.5,
-        },
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/bandit2.ase")),
-            movement_type: MovementType::None,
-            speed: 0.0,
-            attack_type: AttackType::Shoot(1),
-            attack_delay: 2.0,
-        },
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/demo_bandit.ase")),
-            movement_type: MovementType::Wander,
-            speed: 16.0,
-            attack_type: AttackType::ShootAfter(2),
-            attack_delay: 2.0,
-        },
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/laser.ase")),
-            movement_type: MovementType::None,
-            attack_type: AttackType::ShootAfter(4),
-            speed: 0.0,
-            attack_delay: 2.0,
-        },
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/bat.ase")),
-            movement_type: MovementType::FollowPath,
-            attack_type: AttackType::Melee,
-            speed: 5.0,
-            attack_delay: 0.0,
-        },
-        EnemyType {
-            animation: AnimationsGroup::from_file(include_bytes!("../assets/skeleton.ase")),
-            movement_type: MovementType::Chase,
-            attack_type: AttackType::Melee,
-            speed: 32.0,
-            attack_delay: 0.0,
-        },
-    ]
+/// Every enemy type along with its animations, in the same order as [ENEMY_DEFINITIONS].
+/// Has to be read again after [ENEMY_DEFINITIONS] is.
+pub static ENEMIES: Reloadable<Vec<EnemyType>> = Reloadable::new("enemies.toml", || {
+    Ok(ENEMY_DEFINITIONS
+        .iter()
+        .map(|f| EnemyType {
+            animation: AnimationsGroup::from_file(&read_named_asset(&f.sprite).unwrap()),
+            movement: f.movement.clone(),
+            attack: f.attack.clone(),
+            attack_delay: f.attack_delay,
+            speed: f.speed,
+            hitbox: f.hitbox,
+            health: f.health,
+            aim: f.aim,
+            range: f.range,
+        })
+        .collect())
 });
+
+#[test]
+fn enemy_definitions_are_validated() {
+    let names: Vec<&str> = ENEMY_DEFINITIONS.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "bandit",
+            "standing_bandit",
+            "demo_bandit",
+            "laser",
+            "bat",
+            "skeleton"
+        ]
+    );
+    assert_eq!(
+        format!("{:?}", ENEMY_DEFINITIONS[3].attack),
+        "Shoot { projectile: 4, after_animation: true }"
+    );
+
+    let sprite_exists = |f: &str| f == "skeleton.ase";
+    let fast_skeleton = "[[enemies]]
+name = \"fast_skeleton\"
+sprite = \"skeleton.ase\"
+movement = \"chase\"
+speed = 64.0
+attack = \"melee\"
+hitbox = [0, 0, 8, 8]
+health = 3
+";
+    let enemies = parse_enemy_definitions(fast_skeleton, sprite_exists).unwrap();
+    assert_eq!(
+        format!("{:?}", enemies[0].movement),
+        "Chase { jump_height: 0, max_drop: 3 }"
+    );
+    assert_eq!(enemies[0].hitbox, Rect::new(0.0, 0.0, 8.0, 8.0));
+    assert_eq!(enemies[0].health, 3);
+
+    let no_projectile = fast_skeleton.replace("melee", "shoot");
+    assert!(parse_enemy_definitions(&no_projectile, sprite_exists).is_err());
+    let unknown_movement = fast_skeleton.replace("chase", "teleport");
+    assert!(parse_enemy_definitions(&unknown_movement, sprite_exists).is_err());
+    let hop_without_height = fast_skeleton.replace("chase", "hop");
+    assert!(parse_enemy_definitions(&hop_without_height, sprite_exists).is_err());
+    let missing_sprite = fast_skeleton.replace("skeleton.ase", "ghost.ase");
+    assert!(parse_enemy_definitions(&missing_sprite, sprite_exists).is_err());
+    let twice = format!("{fast_skeleton}\n{fast_skeleton}");
+    assert!(parse_enemy_definitions(&twice, sprite_exists).is_err());
+}
+
+/// Makes an enemy at `pos` out of an enemy type written like in `enemies.toml`, without loading its sprite
+#[cfg(test)]
+fn test_enemy(pos: Vec2, definition: &str) -> Enemy {
+    let definition = parse_enemy_definitions(&format!("[[enemies]]\n{definition}"), |_| true)
+        .unwrap()
+        .remove(0);
+    let ty = Box::leak(Box::new(EnemyType {
+        animation: AnimationsGroup {
+            file: asefile::AsepriteFile::read(&asset!("skeleton.ase")[..]).unwrap(),
+            // an attack animation that's over straight away
+            animations: vec![crate::assets::Animation {
+                frames: Vec::new(),
+                total_length: 0,
+            }],
+            tag_names: [("attack".to_string(), 0)].into(),
+        },
+        movement: definition.movement,
+        attack: definition.attack,
+        attack_delay: definition.attack_delay,
+        speed: definition.speed,
+        hitbox: definition.hitbox,
+        health: definition.health,
+        aim: definition.aim,
+        range: definition.range,
+    }));
+    Enemy {
+        pos,
+        velocity: Vec2::ZERO,
+        ty,
+        path_index: None,
+        time: 0.0,
+        attacks: 0,
+        attack_time: 0.0,
+        death_frames: 0.0,
+        health: ty.health,
+        invulnerable: 0.0,
+        grounded: false,
+        state: ty.movement.new_state(),
+        wibble_wobble: 0.0,
+        spawner: None,
+    }
+}
+
+#[test]
+fn enemies_take_damage_between_invulnerability() {
+    let mut enemy = test_enemy(
+        vec2(16.0, 16.0),
+        "name = \"skeleton\"
+sprite = \"skeleton.ase\"
+movement = \"chase\"
+attack = \"melee\"
+health = 3",
+    );
+    let bullet = |damage| {
+        let mut projectile = Projectile::new(0, vec2(20.0, 20.0), vec2(-1.0, 0.0));
+        projectile.damage = damage;
+        projectile
+    };
+
+    let mut projectiles = vec![bullet(1), bullet(1)];
+    enemy.take_hits(&mut projectiles);
+    assert_eq!(enemy.health, 2);
+    assert!(projectiles[0].dead && !projectiles[1].dead);
+    assert!(enemy.velocity.x < 0.0);
+    assert_eq!(enemy.velocity.y, 0.0);
+
+    // still invulnerable, so the second bullet passes through
+    enemy.take_hits(&mut projectiles[1..]);
+    assert_eq!(enemy.health, 2);
+    assert!(!projectiles[1].dead);
+
+    enemy.invulnerable = 0.0;
+    enemy.take_hits(&mut [bullet(5)]);
+    assert_eq!(enemy.health, 0);
+}
+
+#[test]
+fn aiming_enemies_shoot_at_the_player() {
+    let definition = "name = \"bandit\"
+sprite = \"bandit.ase\"
+movement = \"none\"
+attack = \"shoot\"
+projectile = 1";
+    let player = Player::new(vec2(-60.0, 80.0));
+    let mut projectiles = Vec::new();
+
+    test_enemy(vec2(0.0, 0.0), definition).shoot(1, false, &player, &mut projectiles);
+    let aiming = test_enemy(vec2(0.0, 0.0), &format!("{definition}\naim = true"));
+    aiming.shoot(1, aiming.ty.aim, &player, &mut projectiles);
+
+    assert_eq!(projectiles[0].direction.normalize(), vec2(-1.0, 0.0));
+    assert_eq!(projectiles[0].pos, vec2(-4.0, 0.0));
+    // from the muzzle to the player's centre
+    let aimed = (player.pos + 4.0 - vec2(4.0, 0.0)).normalize();
+    assert!(
+        projectiles[1]
+            .direction
+            .normalize()
+            .abs_diff_eq(aimed, 0.001)
+    );
+}
+
+/// Runs an enemy at 60 frames per second, returning the projectiles it fired
+#[cfg(test)]
+fn run_enemy(
+    enemy: &mut Enemy,
+    player: &mut Player,
+    level: &Level,
+    seconds: f32,
+) -> Vec<Projectile> {
+    let mut projectiles = Vec::new();
+    for _ in 0..(seconds * 60.0).round() as usize {
+        enemy.think(player, &mut projectiles, level, 1.0 / 60.0);
+    }
+    projectiles
+}
@@ -0,0 +1,189 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+    enemies::{Enemy, MakeBehaviour, RawEnemyDefinition},
+    player::Player,
+    projectiles::Projectile,
+};
+
+/// Seconds between the shots of a turret's burst
+const TURRET_INTERVAL: f32 = 0.2;
+
+/// How an enemy attacks the player. Attacks start when the player is in the enemy's range, and after they're over
+/// the enemy waits its attack delay before attacking again.
+pub trait Attack: Debug + Send + Sync {
+    /// Whether the enemy waits until nothing solid is between it and the player to attack
+    fn needs_sight(&self) -> bool {
+        false
+    }
+    /// Starts an attack, as the enemy's attack animation starts playing.
+    /// Attacks that are already over return false, and don't play the animation or wait the attack delay.
+    fn start(
+        &self,
+        enemy: &mut Enemy,
+        player: &mut Player,
+        projectiles: &mut Vec<Projectile>,
+    ) -> bool;
+    /// Carries on an attack every frame, `time` seconds after the enemy's attack animation ended.
+    /// Negative while it's still playing.
+    #[allow(unused_variables)]
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        player: &Player,
+        projectiles: &mut Vec<Projectile>,
+        time: f32,
+    ) {
+    }
+    /// How long the attack carries on after the attack animation, before the attack delay starts, in seconds
+    fn length(&self) -> f32 {
+        0.0
+    }
+}
+
+/// Every attack `enemies.toml` can name, and how to make it from the rest of the enemy's definition
+pub(super) const ATTACKS: &[(&str, MakeBehaviour<dyn Attack>)] = &[
+    ("none", |_| Ok(Arc::new(NoAttack))),
+    ("melee", |_| Ok(Arc::new(Melee))),
+    ("shoot", |raw| {
+        Ok(Arc::new(Shoot {
+            projectile: projectile(raw)?,
+            after_animation: false,
+        }))
+    }),
+    ("shoot_after", |raw| {
+        Ok(Arc::new(Shoot {
+            projectile: projectile(raw)?,
+            after_animation: true,
+        }))
+    }),
+    ("turret", |raw| {
+        Ok(Arc::new(Turret {
+            projectile: projectile(raw)?,
+            shots: raw.shots.unwrap_or(3),
+        }))
+    }),
+];
+
+/// Gets the projectile a shooting enemy fires
+fn projectile(raw: &RawEnemyDefinition) -> Result<usize, String> {
+    raw.projectile
+        .ok_or_else(|| format!("enemy {:?} shoots, but has no projectile", raw.name))
+}
+
+#[derive(Debug)]
+pub struct NoAttack;
+impl Attack for NoAttack {
+    fn start(&self, _: &mut Enemy, _: &mut Player, _: &mut Vec<Projectile>) -> bool {
+        false
+    }
+}
+
+/// Kills the player on touch
+#[derive(Debug)]
+pub struct Melee;
+impl Attack for Melee {
+    fn start(&self, enemy: &mut Enemy, player: &mut Player, _: &mut Vec<Projectile>) -> bool {
+        if (player.pos + 4.0).distance(enemy.pos + 4.0) < 5.0 {
+            player.death = Some((0.0, 0, true))
+        }
+        false
+    }
+}
+
+/// Fires a projectile at the player, as the attack animation starts or once it's over
+#[derive(Debug)]
+pub struct Shoot {
+    pub projectile: usize,
+    pub after_animation: bool,
+}
+impl Attack for Shoot {
+    fn needs_sight(&self) -> bool {
+        // enemies shooting at the player wait until they can see them. lasers fire regardless.
+        Projectile::shoot_offset(self.projectile)
+    }
+    fn start(
+        &self,
+        enemy: &mut Enemy,
+        player: &mut Player,
+        projectiles: &mut Vec<Projectile>,
+    ) -> bool {
+        if !self.after_animation {
+            enemy.shoot(self.projectile, enemy.ty.aim, player, projectiles);
+            enemy.attacks = 1;
+        }
+        true
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        player: &Player,
+        projectiles: &mut Vec<Projectile>,
+        time: f32,
+    ) {
+        if time >= 0.0 && enemy.attacks == 0 {
+            enemy.shoot(self.projectile, enemy.ty.aim, player, projectiles);
+            enemy.attacks = 1;
+        }
+    }
+}
+
+/// Fires a burst of shots straight at the player once the attack animation is over
+#[derive(Debug)]
+pub struct Turret {
+    pub projectile: usize,
+    /// How many shots are in each burst
+    pub shots: u32,
+}
+impl Attack for Turret {
+    fn needs_sight(&self) -> bool {
+        Projectile::shoot_offset(self.projectile)
+    }
+    fn start(&self, _: &mut Enemy, _: &mut Player, _: &mut Vec<Projectile>) -> bool {
+        true
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        player: &Player,
+        projectiles: &mut Vec<Projectile>,
+        time: f32,
+    ) {
+        if enemy.attacks < self.shots && time >= enemy.attacks as f32 * TURRET_INTERVAL {
+            enemy.shoot(self.projectile, true, player, projectiles);
+            enemy.attacks += 1;
+        }
+    }
+    fn length(&self) -> f32 {
+        self.shots.saturating_sub(1) as f32 * TURRET_INTERVAL
+    }
+}
+
+#[test]
+fn turrets_fire_bursts_at_the_player() {
+    use crate::{
+        assets::{Level, test_map},
+        enemies::{run_enemy, test_enemy},
+    };
+    use macroquad::prelude::*;
+
+    let level = Level::new(test_map(&["################"]));
+    let mut player = Player::new(vec2(12.0 * 8.0, 10.0 * 8.0));
+    let mut turret = test_enemy(
+        vec2(8.0, 14.0 * 8.0),
+        "name = \"turret\"
+sprite = \"bandit.ase\"
+movement = \"none\"
+attack = \"turret\"
+projectile = 1
+attack_delay = 2.0",
+    );
+
+    let projectiles = run_enemy(&mut turret, &mut player, &level, 1.0);
+    assert_eq!(projectiles.len(), 3);
+    // every shot is aimed up at the player
+    assert!(projectiles.iter().all(|f| f.direction.y < 0.0));
+    // waits out the attack delay before the next burst
+    let projectiles = run_enemy(&mut turret, &mut player, &level, 1.0);
+    assert!(projectiles.is_empty());
+}
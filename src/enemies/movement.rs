@@ -0,0 +1,601 @@
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use macroquad::prelude::*;
+
+use crate::{
+    assets::{Level, LinkKind, NavLink, tile_of},
+    enemies::{Enemy, MakeBehaviour, RawEnemyDefinition},
+    player::Player,
+    utils::GRAVITY,
+};
+
+/// How much higher than where they land chasing enemies jump, so they clear the edge
+const JUMP_CLEARANCE: f32 = 8.0;
+/// Seconds hopping enemies wait on the ground between hops
+const HOP_INTERVAL: f32 = 0.6;
+/// How far hovering enemies drift sideways from where they're placed, following the player, in pixels
+const HOVER_RANGE: f32 = 48.0;
+/// How far hovering enemies bob up and down, in pixels
+const HOVER_BOB: f32 = 4.0;
+/// How close the player has to be for a charging enemy to charge, in pixels
+const CHARGE_RANGE: f32 = 96.0;
+/// Seconds a charging enemy stands still before charging, so the player can see it coming
+const CHARGE_WIND_UP: f32 = 0.4;
+/// Seconds a charging enemy rests after a charge
+const CHARGE_REST: f32 = 1.0;
+/// How close the player has to get for an enemy lying in ambush to wake up, in pixels
+const AMBUSH_RANGE: f32 = 32.0;
+
+/// How an enemy moves on its own. Every enemy of a type shares its movement, and keeps its own state for it.
+pub trait Movement: Debug + Send + Sync {
+    /// Makes the state an enemy keeps for the movement, which [Movement::update] gets back every frame
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(())
+    }
+    /// Moves the enemy, usually by setting its velocity. Isn't called while the enemy is knocked back.
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        delta_time: f32,
+    );
+    /// Whether the enemy falls
+    fn has_gravity(&self) -> bool {
+        false
+    }
+    /// Whether the enemy collides with the level two tiles high, like the player, instead of one
+    #[allow(unused_variables)]
+    fn is_tall(&self, enemy: &Enemy) -> bool {
+        true
+    }
+    /// Whether getting hit pushes the enemy back, stopping it from moving on its own for a moment
+    #[allow(unused_variables)]
+    fn can_be_knocked_back(&self, enemy: &Enemy) -> bool {
+        true
+    }
+    /// Whether the enemy shows its moving animation
+    fn is_moving(&self, enemy: &Enemy) -> bool {
+        enemy.velocity.x.abs() > 5.0
+    }
+}
+
+/// Every movement `enemies.toml` can name, and how to make it from the rest of the enemy's definition
+pub(super) const MOVEMENTS: &[(&str, MakeBehaviour<dyn Movement>)] = &[
+    ("none", |_| Ok(Arc::new(Stand))),
+    ("wander", |_| Ok(Arc::new(Wander))),
+    ("follow_path", |_| Ok(Arc::new(FollowPath))),
+    ("chase", |raw| Ok(Arc::new(Chase::new(raw)))),
+    ("hop", |raw| Ok(Arc::new(Hop::new(raw)?))),
+    ("hover", |_| Ok(Arc::new(Hover))),
+    ("charge", |_| Ok(Arc::new(Charge))),
+    ("ambush", |raw| Ok(Arc::new(Ambush(Chase::new(raw))))),
+];
+
+/// Gets the state an enemy keeps for a movement
+fn state<T: 'static>(state: &mut dyn Any) -> &mut T {
+    state
+        .downcast_mut()
+        .expect("enemies get their state from their own movement")
+}
+
+/// Stays where it is
+#[derive(Debug)]
+pub struct Stand;
+impl Movement for Stand {
+    fn update(&self, _: &mut Enemy, _: &mut dyn Any, _: &Player, _: &Level, _: f32) {}
+}
+
+/// Walks back and forth at random
+#[derive(Debug)]
+pub struct Wander;
+impl Movement for Wander {
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        _: &mut dyn Any,
+        _: &Player,
+        level: &Level,
+        delta_time: f32,
+    ) {
+        let value = enemy.time + enemy.wibble_wobble;
+        // values for this formula found with `find_lowest_drift_factor`
+        let value =
+            value.sin() * (value * 4.627175 + 1.5).sin() * (value * 5.306475 + 8.0).sin().powi(2);
+        let value = if value.abs() < 0.1 {
+            0.0
+        } else if value.is_sign_positive() {
+            1.0
+        } else {
+            -1.0
+        };
+        enemy.walk(value * enemy.ty.speed, level, delta_time);
+    }
+}
+
+/// Flies along the enemy path it was placed on, at `speed` tiles per second.
+/// Enemies placed away from any path hunt the player instead, around anything solid in the way.
+#[derive(Debug)]
+pub struct FollowPath;
+/// The way a flying enemy found to the player, kept until it's found again
+#[derive(Default)]
+pub(super) struct FlyingPath {
+    /// The enemy's and the player's tiles when it was found
+    pub ends: Option<((i16, i16), (i16, i16))>,
+    pub path: Option<Vec<(i16, i16)>>,
+}
+impl Movement for FollowPath {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(FlyingPath::default())
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        _: f32,
+    ) {
+        let Some((path_index, path_tile_index)) = enemy.path_index else {
+            enemy.fly(self::state(state), player, level);
+            return;
+        };
+        let path = &level.enemy_paths[path_index];
+        let time_per_tile = 1.0 / enemy.ty.speed;
+        let path_time = path.len() as f32 * time_per_tile;
+        let value =
+            (enemy.time + path_tile_index as f32 * time_per_tile) % path_time / time_per_tile;
+        let value_index = value.floor();
+
+        let current = path[value_index as usize];
+        let next = path[(value_index as usize + 1) % path.len()];
+        let amt_between = value - value_index;
+        enemy.pos = current.lerp(next, amt_between);
+    }
+    fn is_tall(&self, enemy: &Enemy) -> bool {
+        // flying enemies' paths go through gaps a tile high
+        enemy.path_index.is_some()
+    }
+    fn can_be_knocked_back(&self, enemy: &Enemy) -> bool {
+        // enemies following a path can't be pushed off it
+        enemy.path_index.is_none()
+    }
+    fn is_moving(&self, _: &Enemy) -> bool {
+        true
+    }
+}
+
+/// Walks towards the player along the level's navigation, jumping and dropping onto other spans the enemy can get to.
+/// Enemies that can't get to the player wait at the end of their span, instead of walking off ledges or into walls.
+#[derive(Debug)]
+pub struct Chase {
+    /// How many tiles up the enemy can jump. Enemies that can't jump have a jump height of zero.
+    pub jump_height: u8,
+    /// How many tiles down the enemy will drop
+    pub max_drop: u8,
+}
+/// Where a jumping enemy lands, and how fast it moves sideways to get there
+#[derive(Default)]
+struct ChaseState(Option<(f32, f32)>);
+impl Chase {
+    fn new(raw: &RawEnemyDefinition) -> Self {
+        Self {
+            jump_height: raw.jump_height,
+            max_drop: raw.max_drop.unwrap_or(3),
+        }
+    }
+    /// Whether the enemy can get across a link in the level's navigation
+    pub fn can_use(&self, link: &NavLink) -> bool {
+        let drop_is_safe = -link.rise <= self.max_drop as i16;
+        match link.kind {
+            LinkKind::Step => true,
+            LinkKind::Drop => drop_is_safe,
+            LinkKind::Jump => {
+                self.jump_height > 0 && link.rise <= self.jump_height as i16 && drop_is_safe
+            }
+        }
+    }
+    /// Jumps high enough to get up to where the link lands, and fast enough to get across to it
+    fn jump_along(enemy: &mut Enemy, state: &mut ChaseState, link: &NavLink) {
+        let rise = link.rise as f32 * 8.0;
+        let speed = (2.0 * GRAVITY * (rise.max(0.0) + JUMP_CLEARANCE)).sqrt();
+        // how long until the enemy comes back down to where it lands
+        let time = (speed + (speed * speed - 2.0 * GRAVITY * rise).sqrt()) / GRAVITY;
+        let landing = link.landing as f32 * 8.0;
+        let speed_x = (landing - enemy.pos.x) / time;
+        enemy.velocity = vec2(speed_x, -speed);
+        state.0 = Some((landing, speed_x.abs()));
+    }
+}
+impl Movement for Chase {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(ChaseState::default())
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        delta_time: f32,
+    ) {
+        let state: &mut ChaseState = self::state(state);
+        if !enemy.grounded {
+            // jumps stopped by a corner on the way up carry on once they're past it
+            if let Some((landing, speed)) = state.0
+                && enemy.velocity.x == 0.0
+                && (landing - enemy.pos.x).abs() > 1.0
+            {
+                enemy.velocity.x = speed.max(enemy.ty.speed) * (landing - enemy.pos.x).signum();
+            }
+            return;
+        }
+        state.0 = None;
+        let navigation = level.navigation();
+        let tile = tile_of(enemy.pos);
+        let Some(span) = navigation.span_at(tile) else {
+            // somewhere the navigation doesn't cover, like on a moving platform
+            enemy.walk_towards(player.pos.x, level, delta_time);
+            return;
+        };
+        let route = navigation
+            .span_below(tile_of(player.pos))
+            .filter(|goal| *goal != span)
+            .and_then(|goal| navigation.find_route(span, tile.0, goal, |f| self.can_use(f)));
+        let target = match route.as_ref().and_then(|f| f.first()) {
+            Some(&link) => {
+                let link = navigation.links[link];
+                let takeoff = link.takeoff as f32 * 8.0;
+                if link.kind != LinkKind::Jump {
+                    // walks off the end of the span, onto the next one
+                    link.landing as f32 * 8.0
+                } else if (enemy.pos.x - takeoff).abs() <= 1.0 {
+                    Self::jump_along(enemy, state, &link);
+                    return;
+                } else {
+                    takeoff
+                }
+            }
+            None => {
+                let span = navigation.spans[span];
+                player
+                    .pos
+                    .x
+                    .clamp(span.start as f32 * 8.0, span.end as f32 * 8.0)
+            }
+        };
+        enemy.walk_towards(target, level, delta_time);
+    }
+    fn has_gravity(&self) -> bool {
+        true
+    }
+}
+
+/// Hops towards the player every so often, `jump_height` tiles high and at `speed` pixels per second
+#[derive(Debug)]
+pub struct Hop {
+    /// How fast the enemy leaves the ground, in pixels per second
+    pub hop_speed: f32,
+}
+/// Seconds until the enemy hops again
+struct HopState(f32);
+impl Hop {
+    fn new(raw: &RawEnemyDefinition) -> Result<Self, String> {
+        if raw.jump_height == 0 {
+            return Err(format!("enemy {:?} hops, but has no jump_height", raw.name));
+        }
+        let height = raw.jump_height as f32 * 8.0;
+        Ok(Self {
+            hop_speed: (2.0 * GRAVITY * height).sqrt(),
+        })
+    }
+}
+impl Movement for Hop {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(HopState(HOP_INTERVAL))
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        delta_time: f32,
+    ) {
+        let HopState(wait) = self::state(state);
+        if !enemy.grounded {
+            return;
+        }
+        enemy.walk(0.0, level, delta_time);
+        *wait -= delta_time;
+        if *wait <= 0.0 && player.death.is_none() {
+            *wait = HOP_INTERVAL;
+            let direction = (player.pos.x - enemy.pos.x).signum();
+            enemy.velocity = vec2(direction * enemy.ty.speed, -self.hop_speed);
+        }
+    }
+    fn has_gravity(&self) -> bool {
+        true
+    }
+    fn is_moving(&self, enemy: &Enemy) -> bool {
+        !enemy.grounded
+    }
+}
+
+/// Floats around where it's placed, bobbing up and down, and drifting sideways to stay above or below the player
+#[derive(Debug)]
+pub struct Hover;
+/// Where the enemy was placed, once it starts moving
+struct HoverState(Option<Vec2>);
+impl Movement for Hover {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(HoverState(None))
+    }
+    fn update(&self, enemy: &mut Enemy, state: &mut dyn Any, player: &Player, _: &Level, _: f32) {
+        let HoverState(anchor) = self::state(state);
+        let anchor = *anchor.get_or_insert(enemy.pos);
+        let target = vec2(
+            player
+                .pos
+                .x
+                .clamp(anchor.x - HOVER_RANGE, anchor.x + HOVER_RANGE),
+            anchor.y + (enemy.time * 2.0 + enemy.wibble_wobble).sin() * HOVER_BOB,
+        );
+        enemy.velocity = ((target - enemy.pos) * 4.0).clamp_length_max(enemy.ty.speed);
+    }
+    fn is_tall(&self, _: &Enemy) -> bool {
+        false
+    }
+    fn is_moving(&self, _: &Enemy) -> bool {
+        true
+    }
+}
+
+/// Waits for the player to come along the floor it's on, then charges at them at `speed` until it hits a wall or
+/// gets to the edge of the floor
+#[derive(Debug)]
+pub struct Charge;
+#[derive(Clone, Copy)]
+enum ChargeState {
+    Idle,
+    /// - seconds spent winding up
+    /// - direction of the charge
+    WindingUp(f32, f32),
+    /// - direction of the charge
+    /// - whether the charge has got going, so stopping means it hit something
+    Charging(f32, bool),
+    /// - seconds spent resting
+    Resting(f32),
+}
+impl Movement for Charge {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(ChargeState::Idle)
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        delta_time: f32,
+    ) {
+        let state: &mut ChargeState = self::state(state);
+        if !enemy.grounded {
+            return;
+        }
+        let speed = match state {
+            ChargeState::Idle => {
+                let distance = player.pos - enemy.pos;
+                if player.death.is_none()
+                    && distance.y.abs() < 8.0
+                    && distance.x.abs() < CHARGE_RANGE
+                    && enemy.can_see(player, level)
+                {
+                    *state = ChargeState::WindingUp(0.0, distance.x.signum());
+                }
+                0.0
+            }
+            ChargeState::WindingUp(time, direction) => {
+                *time += delta_time;
+                if *time >= CHARGE_WIND_UP {
+                    *state = ChargeState::Charging(*direction, false);
+                }
+                0.0
+            }
+            ChargeState::Charging(direction, going) => {
+                let floor = level
+                    .navigation()
+                    .span_at(tile_of(enemy.pos))
+                    .map(|f| level.navigation().spans[f]);
+                let end = floor.map(|f| if *direction > 0.0 { f.end } else { f.start });
+                let blocked = *going && enemy.velocity.x == 0.0;
+                if blocked || end.is_none_or(|f| (f as f32 * 8.0 - enemy.pos.x).abs() <= 1.0) {
+                    *state = ChargeState::Resting(0.0);
+                    0.0
+                } else {
+                    *going = true;
+                    *direction * enemy.ty.speed
+                }
+            }
+            ChargeState::Resting(time) => {
+                *time += delta_time;
+                if *time >= CHARGE_REST {
+                    *state = ChargeState::Idle;
+                }
+                0.0
+            }
+        };
+        enemy.walk(speed, level, delta_time);
+    }
+    fn has_gravity(&self) -> bool {
+        true
+    }
+}
+
+/// Lies still until the player comes close or hits it, then chases them
+#[derive(Debug)]
+pub struct Ambush(pub Chase);
+/// - whether the enemy has woken up
+/// - the state it chases the player with
+struct AmbushState(bool, Box<dyn Any>);
+impl Movement for Ambush {
+    fn new_state(&self) -> Box<dyn Any> {
+        Box::new(AmbushState(false, self.0.new_state()))
+    }
+    fn update(
+        &self,
+        enemy: &mut Enemy,
+        state: &mut dyn Any,
+        player: &Player,
+        level: &Level,
+        delta_time: f32,
+    ) {
+        let AmbushState(awake, chase) = self::state(state);
+        *awake |= enemy.health < enemy.ty.health
+            || (enemy.pos.distance(player.pos) < AMBUSH_RANGE && enemy.can_see(player, level));
+        if *awake {
+            self.0
+                .update(enemy, chase.as_mut(), player, level, delta_time);
+        } else {
+            enemy.walk(0.0, level, delta_time);
+        }
+    }
+    fn has_gravity(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn chasing_enemies_jump_onto_platforms() {
+    use crate::{assets::test_map, enemies::run_enemy, enemies::test_enemy};
+
+    let level = Level::new(test_map(&[
+        "           #####",
+        "                ",
+        "#####   ########",
+    ]));
+    // on the ledge above the far side of the pit
+    let mut player = Player::new(vec2(13.0 * 8.0, 12.0 * 8.0));
+    let skeleton = "name = \"skeleton\"
+sprite = \"skeleton.ase\"
+movement = \"chase\"
+speed = 32.0
+attack = \"melee\"";
+    let mut chase = |definition: &str| {
+        let mut enemy = test_enemy(vec2(8.0, 14.0 * 8.0), definition);
+        run_enemy(&mut enemy, &mut player, &level, 10.0);
+        tile_of(enemy.pos)
+    };
+
+    // across the pit, then up onto the ledge
+    let (x, y) = chase(&format!("{skeleton}\njump_height = 2"));
+    assert!(x >= 11 && y == 12, "{x} {y}");
+    // waits at the edge of the pit
+    assert_eq!(chase(skeleton), (4, 14));
+    // can't jump high enough for the ledge, so doesn't cross the pit either
+    assert_eq!(chase(&format!("{skeleton}\njump_height = 1")), (4, 14));
+}
+
+#[test]
+fn enemies_hop_charge_and_ambush() {
+    use crate::{assets::test_map, enemies::run_enemy, enemies::test_enemy};
+
+    let level = Level::new(test_map(&["################"]));
+    let mut player = Player::new(vec2(12.0 * 8.0, 14.0 * 8.0));
+    let start = vec2(8.0, 14.0 * 8.0);
+    let enemy = |movement: &str| {
+        test_enemy(
+            start,
+            &format!(
+                "name = \"test\"
+sprite = \"skeleton.ase\"
+movement = \"{movement}\"
+speed = 96.0
+jump_height = 1
+health = 2
+attack = \"none\""
+            ),
+        )
+    };
+
+    let mut hopper = enemy("hop");
+    run_enemy(&mut hopper, &mut player, &level, 0.2);
+    assert_eq!(hopper.pos, start);
+    // hops towards the player, and lands again
+    run_enemy(&mut hopper, &mut player, &level, 0.5);
+    assert!(hopper.pos.x > start.x && hopper.pos.y < start.y);
+    run_enemy(&mut hopper, &mut player, &level, 0.3);
+    assert!(hopper.grounded);
+
+    let mut charger = enemy("charge");
+    // winds up before charging
+    run_enemy(&mut charger, &mut player, &level, 0.3);
+    assert_eq!(charger.pos, start);
+    // runs past the player, to the wall at the end of the floor
+    run_enemy(&mut charger, &mut player, &level, 1.5);
+    assert_eq!(tile_of(charger.pos), (15, 14));
+    assert_eq!(charger.velocity.x, 0.0);
+
+    let mut ambusher = enemy("ambush");
+    run_enemy(&mut ambusher, &mut player, &level, 1.0);
+    assert_eq!(ambusher.pos, start);
+    // wakes up once hit
+    ambusher.health = 1;
+    run_enemy(&mut ambusher, &mut player, &level, 1.0);
+    assert!(ambusher.pos.x > start.x);
+}
+
+#[test]
+fn springs_launch_enemies_with_gravity() {
+    use crate::{assets::test_map, enemies::run_enemy, enemies::test_enemy};
+
+    let level = Level::new(test_map(&["sss#############"]));
+    let mut player = Player::new(vec2(12.0 * 8.0, 14.0 * 8.0));
+    let enemy = |movement: &str| {
+        test_enemy(
+            vec2(8.0, 14.0 * 8.0),
+            &format!(
+                "name = \"test\"
+sprite = \"skeleton.ase\"
+movement = \"{movement}\"
+attack = \"none\""
+            ),
+        )
+    };
+
+    let mut chaser = enemy("chase");
+    run_enemy(&mut chaser, &mut player, &level, 0.1);
+    assert!(chaser.pos.y < 13.0 * 8.0 && chaser.velocity.y < 0.0);
+    // floating enemies aren't pulled back down, so stay put
+    let mut hoverer = enemy("hover");
+    run_enemy(&mut hoverer, &mut player, &level, 0.1);
+    assert_eq!(hoverer.pos.y, 14.0 * 8.0);
+}
+
+#[test]
+fn flying_enemies_fly_straight_at_players_they_cant_reach() {
+    use crate::{assets::test_map, enemies::run_enemy, enemies::test_enemy};
+
+    let level = Level::new(test_map(&[
+        "       ###      ",
+        "       # #      ",
+        "################",
+    ]));
+    // walled in, so there's no way to them
+    let mut player = Player::new(vec2(8.0 * 8.0, 14.0 * 8.0));
+    let start = vec2(2.0 * 8.0, 10.0 * 8.0);
+    let mut bat = test_enemy(
+        start,
+        "name = \"bat\"
+sprite = \"bat.ase\"
+movement = \"follow_path\"
+speed = 5.0
+attack = \"none\"",
+    );
+    run_enemy(&mut bat, &mut player, &level, 0.1);
+    assert!(bat.pos.x > start.x && bat.pos.y > start.y, "{}", bat.pos);
+    let FlyingPath { ends, path } = bat.state.downcast_ref().unwrap();
+    assert_eq!(ends.map(|f| f.1), Some((8, 14)));
+    assert!(path.is_none());
+}
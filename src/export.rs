@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+
+use crate::assets::Assets;
+
+/// Options for exporting a level to a PNG, given on the command line as
+/// `export <level> [output] [--scale <n>] [--special]`
+#[derive(Debug, PartialEq)]
+pub struct ExportOptions {
+    pub level: String,
+    /// Defaults to the level's name, with a png extension
+    pub output: Option<PathBuf>,
+    pub scale: u32,
+    /// Whether to draw the special layer
+    pub special: bool,
+}
+impl ExportOptions {
+    /// Returns `None` if the arguments aren't for exporting, or an error describing the problem if they're invalid.
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        let mut args = args.iter();
+        if args.next()? != "export" {
+            return None;
+        }
+        let mut level = None;
+        let mut output = None;
+        let mut scale = 1;
+        let mut special = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--special" => special = true,
+                "--scale" => match args.next().and_then(|f| f.parse().ok()) {
+                    Some(value) if value > 0 => scale = value,
+                    _ => return Some(Err("--scale needs a whole number above 0".to_string())),
+                },
+                _ if level.is_none() => level = Some(arg.clone()),
+                _ if output.is_none() => output = Some(PathBuf::from(arg)),
+                _ => return Some(Err(format!("unexpected argument {arg:?}"))),
+            }
+        }
+        let Some(level) = level else {
+            return Some(Err(
+                "usage: export <level> [output] [--scale <n>] [--special]".to_string(),
+            ));
+        };
+        Some(Ok(Self {
+            level,
+            output,
+            scale,
+            special,
+        }))
+    }
+}
+
+/// Renders a level and saves it as a PNG. Returns where it was saved.
+pub async fn export_level(assets: &Assets, options: &ExportOptions) -> Result<PathBuf, String> {
+    let level = assets
+        .find_level(&options.level)
+        .map(|f| &assets.levels[f])
+        .ok_or_else(|| format!("no level called {:?}", options.level))?;
+    let camera = level.render(&assets.tileset, options.special, options.scale);
+    // let the draw calls go through before reading back the render target
+    next_frame().await;
+
+    let texture = &camera.render_target.as_ref().unwrap().texture;
+    let data = texture.get_texture_data();
+    let image = image::RgbaImage::from_raw(data.width as u32, data.height as u32, data.bytes)
+        .ok_or("render target has the wrong amount of pixels")?;
+    let path = options
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(&level.name).with_extension("png"));
+    image
+        .save(&path)
+        .map_err(|f| format!("couldn't save {}: {f}", path.display()))?;
+    Ok(path)
+}
+
+#[test]
+fn export_arguments_parse() {
+    let args = |args: &str| -> Vec<String> { args.split_whitespace().map(String::from).collect() };
+    assert_eq!(ExportOptions::from_args(&args("0-1 paths")), None);
+    assert_eq!(
+        ExportOptions::from_args(&args("export 1-boss map.png --scale 3 --special")),
+        Some(Ok(ExportOptions {
+            level: "1-boss".to_string(),
+            output: Some(PathBuf::from("map.png")),
+            scale: 3,
+            special: true,
+        }))
+    );
+    assert!(matches!(
+        ExportOptions::from_args(&args("export 0-0 --scale 0")),
+        Some(Err(_))
+    ));
+    assert!(matches!(
+        ExportOptions::from_args(&args("export")),
+        Some(Err(_))
+    ));
+}
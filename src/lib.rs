@@ -0,0 +1,9 @@
+pub mod assets;
+pub mod bosses;
+pub mod enemies;
+pub mod export;
+pub mod player;
+pub mod projectiles;
+pub mod tower;
+pub mod ui;
+pub mod utils;
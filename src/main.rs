@@ -6,50 +6,75 @@ use macroquad::{
     prelude::*,
 };
 
-use crate::{
-    assets::{Assets, Horse, Level},
+use cowboy_tower::{
+    assets::{
+        Assets, Change, Horse, HotReloader, Level, LevelEnd, TILES, TileKind, load_tower,
+        read_level_file,
+    },
     bosses::{Boss, new_boss},
     enemies::*,
-    player::{CinematicBars, Player, update_physicsbody},
+    export::{ExportOptions, export_level},
+    player::{CinematicBars, Player, RayFilter, bounce_on_spring, raycast, update_physicsbody},
     projectiles::*,
     tower::*,
     ui::draw_boss_badges,
     utils::*,
 };
 
-mod assets;
-mod bosses;
-mod enemies;
-mod player;
-mod projectiles;
-mod tower;
-mod ui;
-mod utils;
-
 fn load_enemies(input: Vec<LevelEnemyData>) -> Vec<Enemy> {
     input
         .into_iter()
         .map(|f| Enemy {
             pos: f.pos,
             velocity: Vec2::ZERO,
-            ty: f.ty,
+            ty: &ENEMIES[f.ty],
             time: 0.0,
             path_index: f.path_index,
-            has_attacked: false,
+            attacks: 0,
             spawner: f.spawner,
             death_frames: 0.0,
+            health: ENEMIES[f.ty].health,
+            invulnerable: 0.0,
+            grounded: false,
+            state: ENEMIES[f.ty].movement.new_state(),
             attack_time: -f.attack_delay,
             wibble_wobble: rand::gen_range(0.0, PI * 2.0),
         })
         .collect()
 }
 
+/// Reads the tile registry, enemy types and the rest of the assets again.
+/// The levels are parsed with the new tables, which are put back if anything fails to load, so nothing is replaced unless everything loads.
+fn reload_assets() -> Result<Assets, Vec<String>> {
+    let tiles = TILES.read().map_err(|f| vec![f])?;
+    let definitions = ENEMY_DEFINITIONS.read().map_err(|f| vec![f])?;
+    let old_tiles = TILES.replace(tiles);
+    let old_definitions = ENEMY_DEFINITIONS.replace(definitions);
+    // loads the sprites named by the new enemy types
+    let result = ENEMIES.read().map_err(|f| vec![f]).and_then(|enemies| {
+        let assets = Assets::load()
+            .map_err(|errors| errors.iter().map(ToString::to_string).collect::<Vec<_>>())?;
+        Ok((enemies, assets))
+    });
+    match result {
+        Ok((enemies, assets)) => {
+            ENEMIES.replace(enemies);
+            Ok(assets)
+        }
+        Err(errors) => {
+            TILES.restore(old_tiles);
+            ENEMY_DEFINITIONS.restore(old_definitions);
+            Err(errors)
+        }
+    }
+}
+
 fn get_elevator_pos(assets: &Assets, level_index: usize) -> Vec2 {
     let level = &assets.levels[level_index];
     let elevator_texture = assets.elevator.animations[0].get_at_time(0);
     if let Some(pos) = level.forced_level_end {
         return vec2(
-            if !level_index.is_multiple_of(2) {
+            if level.end == LevelEnd::Left {
                 pos.x
             } else {
                 pos.x - elevator_texture.width() + 8.0
@@ -58,7 +83,7 @@ fn get_elevator_pos(assets: &Assets, level_index: usize) -> Vec2 {
         );
     }
     vec2(
-        if !level_index.is_multiple_of(2) {
+        if level.end == LevelEnd::Left {
             level.player_spawn.x
         } else {
             level.max_pos.x + 16.0 * 8.0 - elevator_texture.width()
@@ -72,7 +97,7 @@ fn get_player_spawn(assets: &Assets, level_index: usize) -> Vec2 {
     if let Some(pos) = level.forced_player_spawn {
         return pos;
     }
-    let left_level_end = !level_index.is_multiple_of(2);
+    let left_level_end = level.end == LevelEnd::Left;
 
     if left_level_end {
         vec2(
@@ -85,7 +110,7 @@ fn get_player_spawn(assets: &Assets, level_index: usize) -> Vec2 {
 }
 
 fn load_boss(level: &Level) -> Option<Box<dyn Boss>> {
-    level.boss.map(|(i, p)| new_boss(i, p))
+    level.boss.map(|(i, p)| new_boss(i, p, level))
 }
 
 fn load_fog_points(level: &Level) -> Vec<FogPoint> {
@@ -106,8 +131,38 @@ struct FogPoint {
     pos: Vec2,
 }
 
-struct Game<'a> {
-    assets: &'a Assets,
+/// How long pieces of destroyed tiles fly for
+const DEBRIS_LIFETIME: f32 = 0.8;
+
+/// A quarter of a destroyed tile, flying away from what destroyed it
+struct Debris {
+    pos: Vec2,
+    velocity: Vec2,
+    time: f32,
+    /// Tile stored as in a level's layer data
+    tile: u16,
+    /// Which quarter of the tile it is, in pixels from the tile's top left corner
+    offset: Vec2,
+}
+/// Splits each destroyed tile into four pieces, flying away from `origin`
+fn create_debris(broken: Vec<(Vec2, u16)>, origin: Vec2) -> impl Iterator<Item = Debris> {
+    broken.into_iter().flat_map(move |(pos, tile)| {
+        [(0.0, 0.0), (4.0, 0.0), (0.0, 4.0), (4.0, 4.0)].map(|(x, y)| {
+            let offset = vec2(x, y);
+            let direction = (pos + offset + 2.0 - origin).normalize_or_zero();
+            Debris {
+                pos: pos + offset,
+                velocity: direction * rand::gen_range(48.0, 96.0) - vec2(0.0, 96.0),
+                time: 0.0,
+                tile,
+                offset,
+            }
+        })
+    })
+}
+
+struct Game {
+    assets: Assets,
     camera: Camera2D,
     ui_camera: Camera2D,
     player: Player,
@@ -124,23 +179,31 @@ struct Game<'a> {
     world_manager: WorldManager,
     gamepad_engine: Gamepads,
     fog_points: Vec<FogPoint>,
+    debris: Vec<Debris>,
+    /// Only set if the reload debug flag is
+    hot_reloader: Option<HotReloader>,
 }
-impl<'a> Game<'a> {
-    fn new(assets: &'a Assets, level: usize) -> Self {
-        let mut y = 0.0;
-        for l in &assets.levels[..level] {
-            y += l.get_height() + FLOOR_PADDING + 16.0;
-        }
-        let world_manager = WorldManager::new(assets);
-        let tower_height = world_manager.world_heights.last().unwrap().1;
-        SKY_MATERIAL.set_uniform("maxTowerHeight", tower_height);
-
+/// The height of the tower below a level
+fn get_level_height(assets: &Assets, level: usize) -> f32 {
+    let mut y = 0.0;
+    for l in &assets.levels[..level] {
+        y += l.get_height() + FLOOR_PADDING + 16.0;
+    }
+    y
+}
+fn create_world_manager(assets: &Assets) -> WorldManager {
+    let world_manager = WorldManager::new(assets);
+    let tower_height = world_manager.world_heights.last().unwrap().1;
+    SKY_MATERIAL.set_uniform("maxTowerHeight", tower_height);
+    world_manager
+}
+impl Game {
+    fn new(assets: Assets, level: usize) -> Self {
         Self {
-            assets,
             level,
-            height: y,
-            world_manager,
-            player: Player::new(get_player_spawn(assets, level)),
+            height: get_level_height(&assets, level),
+            world_manager: create_world_manager(&assets),
+            player: Player::new(get_player_spawn(&assets, level)),
             camera: Camera2D::default(),
             ui_camera: Camera2D::default(),
             enemies: load_enemies(assets.levels[level].enemies.clone()),
@@ -149,11 +212,51 @@ impl<'a> Game<'a> {
             horses: assets.levels[level].horses.clone(),
             gamepad_engine: Gamepads::new(),
             projectiles: Vec::new(),
+            debris: Vec::new(),
             fade_timer: 0.0,
             level_complete: None,
             time: 0.0,
             level_transition_time: 0.0,
+            hot_reloader: DEBUG_FLAGS.reload.then(HotReloader::new),
+            assets,
+        }
+    }
+    /// Reloads changed levels/assets from disk, and restarts the current level with them.
+    /// If they fail to load, the old ones are kept.
+    fn hot_reload(&mut self) {
+        let Some(change) = self.hot_reloader.as_mut().and_then(|f| f.poll()) else {
+            return;
+        };
+        match change {
+            Change::Levels => match load_tower(read_level_file) {
+                Ok((worlds, levels)) => {
+                    self.assets.worlds = worlds;
+                    self.assets.levels = levels;
+                }
+                Err(errors) => {
+                    for error in errors.iter() {
+                        error!("{error}");
+                    }
+                    return;
+                }
+            },
+            Change::Assets => match reload_assets() {
+                Ok(assets) => self.assets = assets,
+                Err(errors) => {
+                    for error in errors.iter() {
+                        error!("{error}");
+                    }
+                    return;
+                }
+            },
         }
+        info!("reloaded, restarting level");
+        let level = self.level.min(self.assets.levels.len() - 1);
+        self.height = get_level_height(&self.assets, level);
+        self.world_manager = create_world_manager(&self.assets);
+        self.level_complete = None;
+        self.level_transition_time = 0.0;
+        self.load_level(level);
     }
     fn load_level(&mut self, level: usize) {
         // Ensure consistent RNG whenever a level is loaded.
@@ -163,14 +266,18 @@ impl<'a> Game<'a> {
         self.level = level;
         self.fog_points = load_fog_points(&self.assets.levels[level]);
         self.projectiles.clear();
+        self.debris.clear();
         self.enemies = load_enemies(self.assets.levels[level].enemies.clone());
         self.boss = load_boss(&self.assets.levels[level]);
         self.horses = self.assets.levels[level].horses.clone();
-        self.player = Player::new(get_player_spawn(self.assets, level));
-        self.player.facing_left = !self.level.is_multiple_of(2);
+        self.assets.levels[level].restart();
+        self.player = Player::new(get_player_spawn(&self.assets, level));
+        self.player.facing_left = self.assets.levels[level].end == LevelEnd::Left;
     }
     fn update(&mut self) {
+        self.hot_reload();
         self.gamepad_engine.poll();
+        let input = Input::read(&mut self.gamepad_engine);
         // cap delta time to a minimum of 60 fps.
         let delta_time = get_frame_time().min(1.0 / 60.0);
         self.time += delta_time;
@@ -179,8 +286,6 @@ impl<'a> Game<'a> {
             .min(actual_screen_height / SCREEN_HEIGHT)
             .floor();
 
-        let elevator_doors_animation = &self.assets.doors.animations[0];
-
         #[cfg(debug_assertions)]
         {
             if is_key_pressed(KeyCode::G) {
@@ -189,7 +294,7 @@ impl<'a> Game<'a> {
         }
 
         if let Some(time) = self.level_complete
-            && time * 1000.0 >= elevator_doors_animation.total_length as f32
+            && time * 1000.0 >= self.assets.doors.animations[0].total_length as f32
         {
             self.level_complete = None;
             self.height += self.assets.levels[self.level].get_height() + FLOOR_PADDING + 16.0;
@@ -200,17 +305,19 @@ impl<'a> Game<'a> {
                 &self.assets.levels[self.level],
                 &mut self.projectiles,
                 &mut self.horses,
-                &mut self.gamepad_engine,
+                &input,
             );
         }
         let level = &self.assets.levels[self.level];
+        level.advance_time(delta_time);
+        let elevator_doors_animation = &self.assets.doors.animations[0];
 
-        let left_level_end = !self.level.is_multiple_of(2);
+        let left_level_end = level.end == LevelEnd::Left;
+        let world = &self.assets.worlds[level.world];
 
-        let elevator_texture =
-            &self.assets.elevator.animations[level.get_world_index() as usize].frames[0].0;
-        let elevator_pos = get_elevator_pos(self.assets, self.level);
-        let player_spawn = get_player_spawn(self.assets, self.level);
+        let elevator_texture = &self.assets.elevator.animations[world.elevator].frames[0].0;
+        let elevator_pos = get_elevator_pos(&self.assets, self.level);
+        let player_spawn = get_player_spawn(&self.assets, self.level);
 
         if self.level_complete.is_none()
             && (self.player.pos.x - (elevator_pos.x + elevator_texture.width() / 2.0)).abs() <= 6.0
@@ -244,15 +351,22 @@ impl<'a> Game<'a> {
                         .lerp(HORSE_SPEED * -horse.direction, 1.0 * delta_time);
                 }
             }
+            if horse.falling {
+                horse.velocity.y += GRAVITY * delta_time;
+            }
             let old_velocity = horse.velocity;
-            (horse.pos, _, _, _) = update_physicsbody(
+            let grounded;
+            (horse.pos, grounded, _, _, _) = update_physicsbody(
                 horse.pos,
                 &mut horse.velocity,
                 delta_time,
                 level,
                 false,
                 false,
+                false,
             );
+            horse.falling &= !grounded;
+            horse.falling |= bounce_on_spring(horse.pos, &mut horse.velocity, level);
             // if horse hits walls / stops, make horse.running = false
             if horse.running
                 && (old_velocity.length() > horse.velocity.length()
@@ -267,8 +381,10 @@ impl<'a> Game<'a> {
                 horse.running = false;
             }
             if horse.running
-                && level.get_tile((horse.pos.x / 8.0) as i16, (horse.pos.y / 8.0) as i16)[3]
-                    == 418 + 1
+                && TILES
+                    .get(level.get_tile((horse.pos.x / 8.0) as i16, (horse.pos.y / 8.0) as i16)[3])
+                    .kind
+                    == TileKind::HorseStop
             {
                 horse.running = false;
                 horse.velocity = Vec2::ZERO;
@@ -277,18 +393,25 @@ impl<'a> Game<'a> {
                 && !horse.running
                 && !horse.player_riding
                 && horse.pos.distance(horse.home_pos) > 1.0
-                && player_tile != 419 + 1
+                && TILES.get(player_tile).kind != TileKind::HorseWait
             {
                 horse.returning_home = true;
             }
         }
 
-        if player_tile == 705 + 1 {
+        let player_tile_kind = TILES.get(player_tile).kind;
+        if player_tile_kind == TileKind::CameraOffset {
             let pos = (self.player.pos / 8.0).floor() * 8.0;
             let camera_offset = level.camera_offsets.iter().find(|f| f.0 == pos).unwrap();
             self.player.camera_offset.set_target(camera_offset.1);
-        } else if player_tile == 704 + 1 {
+        } else if player_tile_kind == TileKind::CameraReset {
             self.player.camera_offset.set_target(0.0);
+        } else if let Some(camera_zone) = level
+            .camera_zones
+            .iter()
+            .find(|f| f.0.contains(self.player.pos))
+        {
+            self.player.camera_offset.set_target(camera_zone.1);
         }
 
         if let Some(time) = &mut self.level_complete {
@@ -313,13 +436,13 @@ impl<'a> Game<'a> {
                 &self.assets.levels[self.level],
                 &mut self.projectiles,
                 &mut self.horses,
-                &mut self.gamepad_engine,
+                &input,
             );
         }
 
         if self.level_transition_time > 0.0 {
             let old = &self.assets.levels[self.level - 1];
-            let elevator_pos = get_elevator_pos(self.assets, self.level - 1);
+            let elevator_pos = get_elevator_pos(&self.assets, self.level - 1);
             let y_diff = (old.min_pos.y - (level.max_pos.y + FLOOR_PADDING)).abs();
             self.level_transition_time -= delta_time;
             self.camera.target = self.player.camera_pos.floor();
@@ -366,7 +489,10 @@ impl<'a> Game<'a> {
         );
         gl_use_default_material();
 
-        let ground_y = self.assets.levels[0].find_marker(2).y;
+        let first_level = &self.assets.levels[0];
+        let ground_y = first_level
+            .find_marker("2")
+            .map_or(first_level.floor_height, |f| f.y);
         draw_rectangle(
             screen_offset.x,
             ground_y - 1.0 + self.height,
@@ -375,7 +501,7 @@ impl<'a> Game<'a> {
             Color::from_hex(0xefb775),
         );
         self.world_manager
-            .draw_tower(self.height, self.assets, self.level);
+            .draw_tower(self.height, &self.assets, self.level);
 
         if self.level > 0 {
             SKY_MATERIAL.set_uniform(
@@ -396,20 +522,20 @@ impl<'a> Game<'a> {
         }
 
         // draw level
-        let t = &level.camera.render_target.as_ref().unwrap().texture;
+        let t = level.texture(&self.assets.tileset);
         draw_texture(t, level.min_pos.x, level.min_pos.y, WHITE);
 
         if self.level_transition_time > 0.0 {
             // draw previous level if during transition
             let old = &self.assets.levels[self.level - 1];
-            let t = &old.camera.render_target.as_ref().unwrap().texture;
+            let t = old.texture(&self.assets.tileset);
             let x = if left_level_end {
                 level.max_pos.x - (old.max_pos.x - old.min_pos.x).abs()
             } else {
                 level.min_pos.x
             };
             draw_texture(t, x, level.max_pos.y + 16.0, WHITE);
-            let elevator_pos = get_elevator_pos(self.assets, self.level - 1)
+            let elevator_pos = get_elevator_pos(&self.assets, self.level - 1)
                 + vec2(
                     x - old.min_pos.x,
                     (old.min_pos.y - (level.max_pos.y + 16.0)).abs(),
@@ -424,7 +550,7 @@ impl<'a> Game<'a> {
                 old.forced_level_end.unwrap_or(old.player_spawn).y - elevator_texture.height()
                     + 8.0
                     - elevator_shaft_height,
-                self.world_manager.world_colors[old.get_world_index() as usize].2,
+                self.assets.worlds[old.world].palette.shaft,
             );
             draw_texture(elevator_texture, elevator_pos.x, elevator_pos.y, WHITE);
             draw_texture(
@@ -443,18 +569,37 @@ impl<'a> Game<'a> {
             elevator_shaft_height,
             elevator_texture.width(),
             elevator_pos.y - elevator_shaft_height,
-            self.world_manager.world_colors[level.get_world_index() as usize].2,
+            world.palette.shaft,
         );
         // draw animated tiles
-        for (pos, index) in level.animated_tiles.iter() {
+        for (pos, tile) in level.animated_tiles.iter() {
+            let Some(animation) = &TILES.get(*tile).animation else {
+                continue;
+            };
             let time = self.time + pos.x.powi(2) + pos.y.powi(2) * 4.2;
-            draw_texture(
-                self.assets.animated_tiles[*index].get_at_time((time * 1000.0) as u32),
+            let frame = animation.get_at_time((time * 1000.0) as u32);
+            let size = vec2(8.0, 8.0 * animation.height as f32);
+            self.assets.tileset.draw_tile(
                 pos.x,
                 pos.y,
-                WHITE,
+                0.0,
+                0.0,
+                Some((
+                    DrawTextureParams {
+                        dest_size: Some(size),
+                        source: Some(Rect::new(
+                            (frame % 32) as f32 * 8.0,
+                            (frame / 32) as f32 * 8.0,
+                            size.x,
+                            size.y,
+                        )),
+                        ..Default::default()
+                    },
+                    WHITE,
+                )),
             );
         }
+        level.draw_moving_platforms(&self.assets.tileset);
         if DEBUG_FLAGS.paths {
             debug_paths(level);
         }
@@ -462,7 +607,7 @@ impl<'a> Game<'a> {
             f.update(
                 &mut self.player,
                 &mut self.projectiles,
-                self.assets,
+                &self.assets,
                 level,
                 delta_time,
             )
@@ -472,7 +617,7 @@ impl<'a> Game<'a> {
         if self.level > 0 {
             let time =
                 (LEVEL_TRANSITION_LENGTH - self.level_transition_time) / LEVEL_TRANSITION_LENGTH;
-            let pos = get_player_spawn(self.assets, self.level);
+            let pos = get_player_spawn(&self.assets, self.level);
             let pos = vec2(
                 pos.x
                     + if left_level_end {
@@ -483,7 +628,7 @@ impl<'a> Game<'a> {
                 pos.y - elevator_texture.height() + 8.0,
             );
             draw_texture(
-                &self.assets.elevator.animations[level.get_world_index() as usize].frames[1].0,
+                &self.assets.elevator.animations[world.elevator].frames[1].0,
                 pos.x,
                 pos.y,
                 WHITE,
@@ -537,14 +682,14 @@ impl<'a> Game<'a> {
 
         if let Some(boss) = &mut self.boss {
             boss.update(
-                self.assets,
+                &self.assets,
                 delta_time,
                 level,
                 &mut self.projectiles,
                 &mut self.player,
             );
         }
-        self.player.draw(self.assets);
+        self.player.draw(&self.assets);
         if let Some(time) = &self.level_complete {
             // draw level end elevator door animation if level complete
             let texture = elevator_doors_animation.get_at_time((*time * 1000.0) as u32);
@@ -558,13 +703,14 @@ impl<'a> Game<'a> {
                 const OFFSET: Vec2 = vec2(4.0, 4.0);
                 projectile.direction.y += GRAVITY * delta_time;
 
-                let (new_pos, on_ground, _, _) = update_physicsbody(
+                let (new_pos, on_ground, _, _, _) = update_physicsbody(
                     projectile.pos - OFFSET,
                     &mut projectile.direction,
                     delta_time,
                     level,
                     false,
                     false,
+                    false,
                 );
                 projectile.pos = new_pos + OFFSET;
                 if on_ground {
@@ -575,7 +721,9 @@ impl<'a> Game<'a> {
                 projectile.pos += projectile.direction * delta_time;
             }
             let rotation = if physics.is_none() {
-                0.0
+                // sprites point right, and are flipped when going left
+                projectile.direction.y.atan2(projectile.direction.x.abs())
+                    * projectile.direction.x.signum()
             } else {
                 projectile.time
                     * 10.0
@@ -588,16 +736,13 @@ impl<'a> Game<'a> {
             let ray_direction = vec2(0.0, 1.0);
             let is_ray = projectile.is_ray();
             let section_count = if is_ray {
-                let tx = (projectile.pos.x / 8.0) as i16;
                 let ty = (projectile.pos.y / 8.0) as i16;
-                let mut count = 0;
-                loop {
-                    if level.get_tile(tx, ty + count * ray_direction.y as i16)[1] != 0 {
-                        break;
-                    }
-                    count += 1;
-                }
-                count - 1
+                // rays that don't hit anything go to the bottom of the level
+                let end = projectile.pos
+                    + ray_direction * (level.max_pos.y + 16.0 * 8.0 - projectile.pos.y);
+                let hit_ty = raycast(projectile.pos, end, level, RayFilter::Solid)
+                    .map_or((end.y / 8.0) as i16, |hit| hit.tile.1);
+                hit_ty - ty - 1
             } else {
                 1
             };
@@ -658,8 +803,10 @@ impl<'a> Game<'a> {
                     for offset in [0.0, -2.0] {
                         let ty = ((projectile.pos.y + offset) / 8.0).floor() as i16;
                         let tile = level.get_tile(tx, ty);
-                        // make projectile hit wall if there is a collision tile or special tile id 672 (projectile barrier)
-                        let hit_wall = tile[1] > 0 || tile[3] == 672 + 1;
+                        // make projectile hit wall if it's inside a collision tile or a projectile barrier
+                        let hit_wall = level
+                            .collides_at(vec2(projectile.pos.x, projectile.pos.y + offset))
+                            || TILES.get(tile[3]).blocks_projectiles;
                         if !hit_wall {
                             didnt_hit_wall = true;
                         }
@@ -670,8 +817,45 @@ impl<'a> Game<'a> {
                     true
                 }
         });
+        for projectile in new_projectiles.iter().filter(|f| f.breaks_tiles()) {
+            let broken = self.assets.levels[self.level].explode(
+                projectile.pos,
+                projectile.get_collision_size(),
+                &self.assets.tileset,
+            );
+            self.debris.extend(create_debris(broken, projectile.pos));
+        }
         self.projectiles.append(&mut new_projectiles);
 
+        // draw debris
+        self.debris.retain_mut(|debris| {
+            debris.time += delta_time;
+            debris.velocity.y += GRAVITY * delta_time;
+            debris.pos += debris.velocity * delta_time;
+            let tile = debris.tile - 1;
+            self.assets.tileset.draw_tile(
+                debris.pos.x.floor(),
+                debris.pos.y.floor(),
+                0.0,
+                0.0,
+                Some((
+                    DrawTextureParams {
+                        dest_size: Some(vec2(4.0, 4.0)),
+                        source: Some(Rect::new(
+                            (tile % 32) as f32 * 8.0 + debris.offset.x,
+                            (tile / 32) as f32 * 8.0 + debris.offset.y,
+                            4.0,
+                            4.0,
+                        )),
+                        rotation: debris.time * 10.0 * debris.velocity.x.signum(),
+                        ..Default::default()
+                    },
+                    WHITE,
+                )),
+            );
+            debris.time < DEBRIS_LIFETIME
+        });
+
         // draw fog
         for point in self.fog_points.iter() {
             let t = &self.assets.clouds.frames[0].0;
@@ -828,7 +1012,7 @@ impl<'a> Game<'a> {
         self.player.time_since_last_boss_defeated += delta_time;
 
         draw_boss_badges(
-            self.assets,
+            &self.assets,
             self.player.time_since_last_boss_defeated,
             self.player.defeated_bosses,
             actual_screen_width / scale_factor,
@@ -876,27 +1060,34 @@ fn window_conf() -> Conf {
 #[macroquad::main(window_conf)]
 async fn main() {
     info!("cowboy tower v{}", env!("CARGO_PKG_VERSION"));
-    let assets = Assets::load();
-    let mut level = 0;
-
-    // load level from command line argument
-    'outer: for arg in args().skip(1) {
-        // check for direct match
-        for (i, l) in assets.levels.iter().enumerate() {
-            if l.name == arg {
-                level = i;
-                break 'outer;
+    let assets = match Assets::load() {
+        Ok(assets) => assets,
+        Err(errors) => {
+            for error in errors.iter() {
+                error!("{error}");
             }
+            panic!("{} level(s) failed to load", errors.len());
         }
-        // check for start of name match
-        for (i, l) in assets.levels.iter().enumerate() {
-            if l.name.starts_with(&arg) {
-                level = i;
-                break 'outer;
-            }
+    };
+    let args: Vec<String> = args().skip(1).collect();
+    if let Some(options) = ExportOptions::from_args(&args) {
+        let result = match options {
+            Ok(options) => export_level(&assets, &options).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(path) => info!("exported level to {}", path.display()),
+            Err(error) => error!("{error}"),
         }
+        return;
     }
-    let mut game = Game::new(&assets, level);
+
+    // load level from command line argument
+    let level = args
+        .iter()
+        .find_map(|f| assets.find_level(f))
+        .unwrap_or_default();
+    let mut game = Game::new(assets, level);
 
     loop {
         game.update();
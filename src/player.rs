@@ -1,10 +1,9 @@
 use std::f32::consts::PI;
 
-use gamepads::Gamepads;
 use macroquad::prelude::*;
 
 use crate::{
-    assets::{Assets, Horse, Level},
+    assets::{Assets, Horse, Level, Surface},
     projectiles::*,
     utils::*,
 };
@@ -14,6 +13,8 @@ mod physics;
 struct ActiveLasso {
     time: f32,
     hook_pos: Vec2,
+    /// Index of the moving platform the lasso is hooked to, which `hook_pos` follows
+    platform: Option<usize>,
     speed: f32,
     lasso_length: f32,
     space_activated: bool,
@@ -77,6 +78,11 @@ impl CameraOffset {
 const HORSE_MOUNT_LEEWAY: f32 = 0.2;
 const JUMP_LAND_LEEWAY: f32 = 0.05;
 const COYOTE_TIME: f32 = 0.05;
+/// How long the player ignores one-way platforms for after dropping through one
+const DROP_THROUGH_TIME: f32 = 0.2;
+
+/// How quickly wind speeds up or slows down a lasso swing, compared to how fast it blows
+const LASSO_WIND_FACTOR: f32 = 3.0;
 
 const MOVE_INABILITY_AFTER_WALL_JUMP: f32 = 0.23;
 const COYOTE_TIME_WALL_JUMP: f32 = 0.15;
@@ -95,7 +101,8 @@ pub struct Player {
     pub active_dialogue: Option<ActiveDialogue>,
     riding: Option<ActiveRiding>,
     active_lasso: Option<ActiveLasso>,
-    lasso_target: Option<Vec2>,
+    /// The nearest lasso target, and the index of the moving platform it's on
+    lasso_target: Option<(Vec2, Option<usize>)>,
     pub death: Option<(f32, usize, bool)>,
     wall_climbing: Option<(f32, f32)>,
     /// Time since last jump off wall
@@ -122,6 +129,9 @@ pub struct Player {
     ///
     /// This gives a bit of leeway when mounting horses mid-air.
     failed_horse_mount_time: f32,
+    /// Set to [DROP_THROUGH_TIME] when the player drops through a one-way platform,
+    /// and platforms are ignored until it reaches 0.0.
+    drop_through_time: f32,
 }
 impl Player {
     pub fn new(pos: Vec2) -> Self {
@@ -143,6 +153,7 @@ impl Player {
             time_since_last_boss_defeated: 10.0,
             defeated_bosses: 0,
             failed_horse_mount_time: 0.0,
+            drop_through_time: 0.0,
             facing_left: false,
             moving: false,
             wall_climbing: None,
@@ -178,7 +189,7 @@ impl Player {
         level: &Level,
         projectiles: &mut Vec<Projectile>,
         horses: &mut [Horse],
-        gamepad_engine: &mut Gamepads,
+        input: &Input,
     ) {
         if let Some(death) = &mut self.death {
             death.0 += delta_time;
@@ -188,20 +199,21 @@ impl Player {
             if death.2 {
                 self.velocity.x = 0.0;
                 self.velocity.y += GRAVITY * delta_time;
-                (self.pos, self.on_ground, _, _) = update_physicsbody(
+                (self.pos, self.on_ground, _, _, _) = update_physicsbody(
                     self.pos,
                     &mut self.velocity,
                     delta_time,
                     level,
                     true,
                     false,
+                    false,
                 );
             }
             return;
         }
         if let Some(dialogue) = &mut self.active_dialogue {
             dialogue.time += delta_time;
-            if is_interact_pressed(gamepad_engine) {
+            if input.interact_pressed {
                 dialogue.closed = true;
             }
             return;
@@ -228,6 +240,9 @@ impl Player {
         if self.jump_of_wall_time < MOVE_INABILITY_AFTER_WALL_JUMP {
             self.jump_of_wall_time += delta_time;
         }
+        if self.drop_through_time > 0.0 {
+            self.drop_through_time -= delta_time;
+        }
         if self.fall_of_wall.0 < COYOTE_TIME_WALL_JUMP {
             self.fall_of_wall.0 += delta_time;
             if self.on_ground {
@@ -238,7 +253,6 @@ impl Player {
         const MOVE_ACCELERATION: f32 = 22.0;
         const JUMP_FORCE: f32 = 160.0;
         self.time += delta_time;
-        let input = get_input_axis(gamepad_engine);
 
         if self.on_ground {
             self.jump_time = 0.0;
@@ -249,7 +263,7 @@ impl Player {
         if self.shooting > 0.0 {
             self.shooting += delta_time;
         } else if self.active_lasso.as_ref().is_none_or(|f| f.time == 0.0)
-            && is_shoot_pressed(gamepad_engine)
+            && input.shoot_pressed
             && self.riding.is_none()
             && self.wall_climbing.is_none()
         {
@@ -269,6 +283,12 @@ impl Player {
 
         if let Some(lasso) = &mut self.active_lasso {
             self.lasso_target = None;
+            if let Some(target) = lasso
+                .platform
+                .and_then(|f| level.moving_platforms[f].lasso_target(level.time()))
+            {
+                lasso.hook_pos = target;
+            }
             lasso.lasso_length = lasso.lasso_length.min(32.0);
             if lasso.time > 0.0 {
                 lasso.time += delta_time;
@@ -294,6 +314,10 @@ impl Player {
                 }
 
                 lasso.speed *= 1.0.lerp(drag_factor, delta_time);
+                // wind pushes the player along the arc
+                let tangent = vec2(-delta.y, delta.x) / lasso.lasso_length;
+                lasso.speed +=
+                    wind_at(self.pos, level) * tangent.x * LASSO_WIND_FACTOR * delta_time;
 
                 let new_angle = angle + lasso.speed * delta_time / lasso.lasso_length;
                 let new_delta_normalized = Vec2::from_angle(new_angle);
@@ -327,42 +351,50 @@ impl Player {
                 self.velocity = self.velocity.lerp(self.velocity * 1.2, delta_time * 5.0);
             }
             if lasso.space_activated {
-                if !is_jump_down(gamepad_engine) && !is_lasso_down(gamepad_engine) {
+                if !input.jump_down && !input.lasso_down {
                     self.active_lasso = None;
                 }
-            } else if !is_lasso_down(gamepad_engine) {
+            } else if !input.lasso_down {
                 self.active_lasso = None;
             }
         } else {
             // find nearest lasso target in direction player is facing
-            let mut targets: Vec<&Vec2> = level
+            let platform_targets = level
+                .moving_platforms
+                .iter()
+                .enumerate()
+                .filter_map(|(i, f)| Some((f.lasso_target(level.time())?, Some(i))));
+            let mut targets: Vec<(Vec2, Option<usize>)> = level
                 .lasso_targets
                 .iter()
-                .filter(|f| {
+                .map(|f| (*f, None))
+                .chain(platform_targets)
+                .filter(|(f, _)| {
                     (if self.facing_left {
                         f.x < self.pos.x
                     } else {
                         f.x > self.pos.x
                     }) && f.distance(self.pos) <= MAX_LASSO_DISTANCE
-                        && raycast(**f, self.pos, level).is_none()
+                        && raycast(*f, self.pos + 4.0, level, RayFilter::Solid).is_none()
                 })
                 .collect();
             self.lasso_target = None;
             if !targets.is_empty() {
-                targets.sort_by(|a, b| {
+                targets.sort_by(|(a, _), (b, _)| {
                     (a.x.powi(2) + a.y.powi(2))
                         .sqrt()
                         .total_cmp(&(b.x.powi(2) + b.y.powi(2)).sqrt())
                 });
-                let closest = *targets[0];
+                let closest = targets[0];
                 self.lasso_target = Some(closest);
             }
-            if is_lasso_pressed(gamepad_engine)
-                && let Some(target) = &self.lasso_target
+            if input.lasso_pressed
+                && let Some((target, platform)) = self.lasso_target
             {
                 self.active_lasso = Some(ActiveLasso {
                     time: delta_time,
-                    hook_pos: *target,
+                    hook_pos: target,
+                    platform,
                     speed: f32::NAN,
                     lasso_length: target.distance(self.pos),
                     in_swing: false,
@@ -372,10 +404,16 @@ impl Player {
             }
 
             if self.jump_of_wall_time >= MOVE_INABILITY_AFTER_WALL_JUMP {
+                let acceleration = if self.on_ground && surface_at(self.pos, level) == Surface::Ice
+                {
+                    ICE_ACCELERATION
+                } else {
+                    MOVE_ACCELERATION
+                };
                 self.velocity.x = self
                     .velocity
                     .x
-                    .lerp(input.x * MOVE_SPEED, delta_time * MOVE_ACCELERATION);
+                    .lerp(input.axis.x * MOVE_SPEED, delta_time * acceleration);
             }
 
             if self.wall_climbing.is_none() || self.velocity.y < 0.0 {
@@ -407,9 +445,9 @@ impl Player {
                 self.velocity.y = amt;
             }
 
-            self.moving = input.x != 0.0;
+            self.moving = input.axis.x != 0.0;
             if self.moving {
-                self.facing_left = input.x.is_sign_negative();
+                self.facing_left = input.axis.x.is_sign_negative();
             }
 
             let wall_jump_state = if let Some((_, direction)) = self.wall_climbing {
@@ -420,8 +458,16 @@ impl Player {
                 None
             };
 
-            if is_jump_pressed(gamepad_engine) {
-                if let Some(direction) = wall_jump_state {
+            if input.jump_pressed {
+                if input.axis.y > 0.0
+                    && self.on_ground
+                    && self.riding.is_none()
+                    && on_platform(self.pos, level)
+                {
+                    // drop through the platform instead of jumping
+                    self.drop_through_time = DROP_THROUGH_TIME;
+                    self.on_ground = false;
+                } else if let Some(direction) = wall_jump_state {
                     self.jump_time = delta_time;
                     self.velocity.y = -JUMP_FORCE * 1.2;
                     self.on_ground = false;
@@ -462,11 +508,12 @@ impl Player {
                     } else {
                         // check if we can lasso
                         if self.active_lasso.is_none()
-                            && let Some(target) = &self.lasso_target
+                            && let Some((target, platform)) = self.lasso_target
                         {
                             self.active_lasso = Some(ActiveLasso {
                                 time: delta_time,
-                                hook_pos: *target,
+                                hook_pos: target,
+                                platform,
                                 speed: f32::NAN,
                                 lasso_length: target.distance(self.pos),
                                 in_swing: false,
@@ -485,11 +532,6 @@ impl Player {
         if !self.on_ground {
             self.last_touched_ground += delta_time;
         }
-        #[cfg(debug_assertions)]
-        let noclip = is_key_down(KeyCode::LeftShift);
-        #[cfg(not(debug_assertions))]
-        let noclip = false;
-
         let old_velocity = self.velocity;
         let touched_death_tile;
         let colliding_with_wall_climb_target;
@@ -497,8 +539,8 @@ impl Player {
         if let Some(riding) = &self.riding {
             self.pos =
                 horses[riding.horse_index].pos + horses[riding.horse_index].get_normal() * 16.0;
-        } else if noclip {
-            self.pos += input.normalize_or_zero() * delta_time * 350.0;
+        } else if input.noclip {
+            self.pos += input.axis.normalize_or_zero() * delta_time * 350.0;
             self.velocity = Vec2::ZERO;
             self.on_ground = false;
         } else {
@@ -507,6 +549,7 @@ impl Player {
                 self.on_ground,
                 touched_death_tile,
                 colliding_with_wall_climb_target,
+                _,
             ) = update_physicsbody(
                 self.pos,
                 &mut self.velocity,
@@ -514,9 +557,14 @@ impl Player {
                 level,
                 true,
                 self.in_boss_battle,
+                self.drop_through_time > 0.0,
             );
             if self.on_ground {
                 self.last_touched_ground = 0.0;
+                if bounce_on_spring(self.pos, &mut self.velocity, level) {
+                    self.jump_time = delta_time;
+                    self.on_ground = false;
+                }
             }
             if let Some(direction) = colliding_with_wall_climb_target {
                 if let Some((time, _)) = &mut self.wall_climbing {
@@ -533,11 +581,10 @@ impl Player {
                 }
                 self.wall_climbing = None;
             }
-            if let Some(tile) = touched_death_tile
+            if let Some(death_animation) = touched_death_tile
                 && self.death.is_none()
             {
-                let death_tile_index = DEATH_TILES.iter().position(|f| *f == tile).unwrap();
-                self.death = Some((0.0, death_tile_index, false));
+                self.death = Some((0.0, death_animation, false));
             }
         }
 
@@ -550,12 +597,14 @@ impl Player {
         let min_x = if level.name != "0-0.tmx" {
             level.min_pos.x
         } else {
-            level.find_marker(3).x
+            level.find_marker("3").map_or(level.min_pos.x, |f| f.x)
         };
         let max_x = if level.name != "0-0.tmx" {
             level.max_pos.x
         } else {
-            level.find_marker(1).x - 16.0 * 8.0 + 8.0
+            level
+                .find_marker("1")
+                .map_or(level.max_pos.x, |f| f.x - 16.0 * 8.0 + 8.0)
         };
         let mut target_camera_pos = self.camera_pos;
         target_camera_pos.x = self
@@ -639,7 +688,7 @@ impl Player {
             return;
         }
 
-        if let Some(target) = &self.lasso_target {
+        if let Some((target, _)) = &self.lasso_target {
             draw_texture_ex(
                 assets.target.get_at_time((self.time * 1000.0) as u32),
                 target.x - 8.0,
@@ -738,3 +787,206 @@ impl Player {
         }
     }
 }
+
+/// A player in a test map, updated a frame at a time
+#[cfg(test)]
+struct Simulation {
+    level: Level,
+    player: Player,
+    horses: Vec<Horse>,
+    projectiles: Vec<Projectile>,
+}
+#[cfg(test)]
+impl Simulation {
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+
+    /// Places the player at `pos` in a map built by [crate::assets::test_map], and lets them land
+    fn new(rows: &[&str], pos: Vec2) -> Self {
+        let level = Level::new(crate::assets::test_map(rows));
+        let mut simulation = Self {
+            horses: level.horses.clone(),
+            level,
+            player: Player::new(pos),
+            projectiles: Vec::new(),
+        };
+        simulation.run(10, Input::default());
+        simulation
+    }
+    /// Updates the player for `frames` frames, with the same input every frame
+    fn run(&mut self, frames: usize, input: Input) {
+        for _ in 0..frames {
+            self.player.update(
+                Self::DELTA_TIME,
+                &self.level,
+                &mut self.projectiles,
+                &mut self.horses,
+                &input,
+            );
+        }
+    }
+    /// Updates the player until `until` returns true, returning how many frames that took
+    fn run_until(&mut self, input: Input, mut until: impl FnMut(&Player) -> bool) -> usize {
+        for frame in 0..600 {
+            if until(&self.player) {
+                return frame;
+            }
+            self.run(1, input);
+        }
+        panic!("player never got there, ended at {}", self.player.pos);
+    }
+}
+
+#[cfg(test)]
+const FLOOR: &[&str] = &["................", "################"];
+
+#[test]
+fn jumps_are_two_tiles_high() {
+    let mut simulation = Simulation::new(FLOOR, vec2(64.0, 112.0));
+    assert!(simulation.player.on_ground);
+    let jump = Input {
+        jump_pressed: true,
+        jump_down: true,
+        ..Default::default()
+    };
+    simulation.run(1, jump);
+    let mut highest = simulation.player.pos.y;
+    let frames = simulation.run_until(Input::default(), |player| {
+        highest = highest.min(player.pos.y);
+        player.on_ground
+    });
+    let height = 112.0 - highest;
+    assert!((16.0..19.0).contains(&height), "jumped {height} pixels");
+    assert!((24..28).contains(&frames), "in the air for {frames} frames");
+}
+
+#[test]
+fn coyote_time_allows_late_jumps() {
+    let ledge = &["................", "########........"];
+    let walk = Input {
+        axis: vec2(1.0, 0.0),
+        ..Default::default()
+    };
+    let jump = Input {
+        jump_pressed: true,
+        ..walk
+    };
+    for (late_frames, jumps) in [(1, true), (2, true), (5, false)] {
+        let mut simulation = Simulation::new(ledge, vec2(40.0, 112.0));
+        simulation.run_until(walk, |player| !player.on_ground);
+        simulation.run(late_frames - 1, walk);
+        simulation.run(1, jump);
+        assert_eq!(
+            simulation.player.velocity.y < 0.0,
+            jumps,
+            "jumping {late_frames} frames after walking off"
+        );
+    }
+}
+
+#[test]
+fn walls_are_climbed_and_jumped_off() {
+    let wall = &[
+        "...........c....",
+        "...........c....",
+        "...........c....",
+        "...........c....",
+        "################",
+    ];
+    let mut simulation = Simulation::new(wall, vec2(56.0, 112.0));
+    let toward_wall = Input {
+        axis: vec2(1.0, 0.0),
+        ..Default::default()
+    };
+    simulation.run(
+        1,
+        Input {
+            jump_pressed: true,
+            jump_down: true,
+            ..toward_wall
+        },
+    );
+    simulation.run_until(toward_wall, |player| player.wall_climbing.is_some());
+    assert!(!simulation.player.on_ground);
+    assert_eq!(simulation.player.pos.x, 80.0);
+    // hangs on the wall for a bit before sliding down
+    let height = simulation.player.pos.y;
+    simulation.run(10, toward_wall);
+    assert_eq!(simulation.player.pos.y, height);
+
+    simulation.run(
+        1,
+        Input {
+            jump_pressed: true,
+            ..toward_wall
+        },
+    );
+    simulation.run(10, toward_wall);
+    assert!(simulation.player.wall_climbing.is_none());
+    assert!(simulation.player.pos.x < 72.0 && simulation.player.pos.y < height - 8.0);
+}
+
+#[test]
+fn lassos_swing_the_player_under_the_target() {
+    let map = &[
+        "..........o.....",
+        "................",
+        "................",
+        "................",
+        "................",
+        "................",
+        "################",
+    ];
+    let mut simulation = Simulation::new(map, vec2(48.0, 112.0));
+    let target = simulation.level.lasso_targets[0];
+    simulation.run(
+        1,
+        Input {
+            lasso_pressed: true,
+            lasso_down: true,
+            ..Default::default()
+        },
+    );
+    assert!(simulation.player.active_lasso.is_some());
+    let hold = Input {
+        lasso_down: true,
+        ..Default::default()
+    };
+    simulation.run_until(hold, |player| {
+        player.active_lasso.as_ref().is_some_and(|f| f.in_swing)
+    });
+    // swings past the target while staying on the rope
+    simulation.run_until(hold, |player| {
+        let lasso = player.active_lasso.as_ref().unwrap();
+        assert!(player.pos.distance(target) <= lasso.lasso_length + 1.0);
+        player.pos.x > target.x + 8.0
+    });
+    assert!(!simulation.player.on_ground);
+
+    simulation.run(1, Input::default());
+    assert!(simulation.player.active_lasso.is_none());
+}
+
+#[test]
+fn horses_are_mounted_with_leeway() {
+    let map = &["....h>..........", "################"];
+    // next to the horse
+    let mut simulation = Simulation::new(map, vec2(24.0, 112.0));
+    let jump = Input {
+        jump_pressed: true,
+        ..Default::default()
+    };
+    simulation.run(1, jump);
+    assert!(simulation.player.riding.is_some());
+    assert!(simulation.horses[0].running && simulation.horses[0].player_riding);
+
+    // falling toward it, jumping a bit too early or far too early
+    for (height, mounts) in [(88.0, true), (40.0, false)] {
+        let mut simulation = Simulation {
+            player: Player::new(vec2(32.0, height)),
+            ..Simulation::new(map, vec2(0.0, 112.0))
+        };
+        simulation.run(1, jump);
+        simulation.run(20, Input::default());
+        assert_eq!(simulation.player.riding.is_some(), mounts, "from {height}");
+    }
+}
@@ -1,40 +1,440 @@
 use macroquad::prelude::*;
 
-use crate::{assets::Level, utils::*};
+use crate::assets::{CollisionShape, Level, Surface, TILES, TileKind};
+
+/// How far up or down a body walking on a slope or half tile can step
+const MAX_STEP: f32 = 4.0;
+/// How far a body can move in one physics step. Moves are split into steps no longer than this,
+/// so fast bodies can't pass through walls.
+const MAX_SUBSTEP: f32 = 4.0;
+/// How fast conveyor belts carry bodies standing on them
+const CONVEYOR_SPEED: f32 = 40.0;
+/// How fast wind pushes bodies inside it
+const WIND_SPEED: f32 = 48.0;
+/// How much of their speed bodies keep while moving sideways through quicksand
+const QUICKSAND_SLOWDOWN: f32 = 0.35;
+/// How fast bodies sink in quicksand
+const SINK_SPEED: f32 = 6.0;
+/// How fast springs launch bodies upwards
+const SPRING_SPEED: f32 = 280.0;
+/// How quickly bodies walking on ice get up to speed, as the part of the difference made up each second
+pub const ICE_ACCELERATION: f32 = 2.0;
 
 fn ceil_g(a: f32) -> f32 {
     if a < 0.0 { a.floor() } else { a.ceil() }
 }
 
-pub fn raycast(from: Vec2, to: Vec2, world: &Level) -> Option<Vec2> {
-    const STEP_SIZE: f32 = 0.5;
-    let mut pos = (from / 8.0).floor();
-    let to = (to / 8.0).floor();
-    let delta = (to - pos).normalize_or_zero();
+/// Which tiles a ray stops at
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RayFilter {
+    /// The solid part of tiles on the collision layer
+    Solid,
+    /// Any tile on the layer with this index
+    Layer(usize),
+    /// Tiles of this kind on the special layer
+    Kind(TileKind),
+}
 
-    while pos.distance(to) > STEP_SIZE {
-        pos += delta * STEP_SIZE;
-        let tx = pos.x.floor();
-        let ty = pos.y.floor();
-        if world.get_tile(tx as _, ty as _)[1] > 0 {
-            return Some(pos);
+/// Where a ray stopped
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayHit {
+    /// Position of the tile that was hit, in tiles
+    pub tile: (i16, i16),
+    /// Where the ray hit the tile
+    pub point: Vec2,
+    /// Points out of the surface that was hit. Rays starting inside a tile get the opposite of their direction.
+    pub normal: Vec2,
+    /// How far the ray went before hitting the tile
+    pub distance: f32,
+}
+
+/// Casts a ray from `from` to `to`, through every tile it passes, and returns the first one matching `filter`
+pub fn raycast(from: Vec2, to: Vec2, level: &Level, filter: RayFilter) -> Option<RayHit> {
+    let length = from.distance(to);
+    let direction = (to - from).normalize_or_zero();
+    let step = vec2(direction.x.signum(), direction.y.signum());
+    let mut tile = (from / 8.0).floor();
+    // distance along the ray between crossing two tile edges, on each axis
+    let delta = (8.0 / direction).abs();
+    // distance along the ray to the next tile edge on each axis
+    let edge_distance = |from: f32, tile: f32, direction: f32| {
+        if direction == 0.0 {
+            f32::INFINITY
+        } else if direction > 0.0 {
+            ((tile + 1.0) * 8.0 - from) / direction
+        } else {
+            (from - tile * 8.0) / -direction
+        }
+    };
+    let mut next = vec2(
+        edge_distance(from.x, tile.x, direction.x),
+        edge_distance(from.y, tile.y, direction.y),
+    );
+    let mut distance = 0.0;
+    // normal of the tile edge the ray came in through
+    let mut normal = -direction;
+    while distance <= length {
+        let exit = next.min_element().min(length);
+        if let Some((hit_distance, surface_normal)) =
+            ray_hits_tile(level, filter, tile, from, direction, distance, exit)
+        {
+            return Some(RayHit {
+                tile: (tile.x as i16, tile.y as i16),
+                point: from + direction * hit_distance,
+                normal: surface_normal.unwrap_or(normal),
+                distance: hit_distance,
+            });
+        }
+        if next.x < next.y {
+            tile.x += step.x;
+            distance = next.x;
+            next.x += delta.x;
+            normal = vec2(-step.x, 0.0);
+        } else {
+            tile.y += step.y;
+            distance = next.y;
+            next.y += delta.y;
+            normal = vec2(0.0, -step.y);
         }
     }
     None
 }
 
+/// Finds where a ray passing through a tile, between `enter` and `exit` distance along it, hits it.
+///
+/// Returns the distance and, if it didn't hit the edge it entered through, the normal of the surface it hit.
+fn ray_hits_tile(
+    level: &Level,
+    filter: RayFilter,
+    tile: Vec2,
+    from: Vec2,
+    direction: Vec2,
+    enter: f32,
+    exit: f32,
+) -> Option<(f32, Option<Vec2>)> {
+    let tile_data = level.get_tile(tile.x as i16, tile.y as i16);
+    let collision = match filter {
+        RayFilter::Solid if tile_data[1] != 0 => TILES.get(tile_data[1]).collision,
+        RayFilter::Layer(layer) if tile_data[layer] != 0 => CollisionShape::Full,
+        RayFilter::Kind(kind) if TILES.get(tile_data[3]).kind == kind => CollisionShape::Full,
+        _ => return None,
+    };
+    // the top and bottom of the solid part are lines across the tile, as (height at left edge, slope)
+    let line = |edge: fn((f32, f32)) -> f32| {
+        let left = edge(collision.span(0.0));
+        (left, (edge(collision.span(8.0)) - left) / 8.0)
+    };
+    let (top, top_slope) = line(|f| f.0);
+    let (bottom, bottom_slope) = line(|f| f.1);
+
+    // the ray is inside the solid part where both of these are positive. rays only touching it don't hit it.
+    // they're linear along the ray, as (value where it enters the tile, change per pixel).
+    let start = from + direction * enter - tile * 8.0;
+    let below_top = (
+        start.y - top - top_slope * start.x,
+        direction.y - top_slope * direction.x,
+    );
+    let above_bottom = (
+        bottom + bottom_slope * start.x - start.y,
+        bottom_slope * direction.x - direction.y,
+    );
+
+    let (mut low, mut high) = (0.0, exit - enter);
+    let mut surface_normal = None;
+    for ((value, change), normal) in [
+        (below_top, vec2(top_slope, -1.0)),
+        (above_bottom, vec2(-bottom_slope, 1.0)),
+    ] {
+        if change == 0.0 {
+            if value <= 0.0 {
+                return None;
+            }
+        } else if change > 0.0 {
+            let crossing = -value / change;
+            if crossing > low {
+                low = crossing;
+                surface_normal = Some(normal.normalize());
+            }
+        } else {
+            high = f32::min(high, -value / change);
+        }
+    }
+    (low < high).then_some((enter + low, surface_normal))
+}
+
+/// Whether a tile on the collision layer is solid, but not a full block
+fn is_shaped(tile: u16) -> bool {
+    let properties = TILES.get(tile);
+    tile != 0 && !properties.deadly && properties.collision != CollisionShape::Full
+}
+
+/// Whether the body at `pos` is standing on a one-way platform, including moving ones
+pub fn on_platform(pos: Vec2, level: &Level) -> bool {
+    let feet = ((pos + vec2(4.0, 8.0)) / 8.0).floor();
+    TILES
+        .get(level.get_tile(feet.x as i16, feet.y as i16)[1])
+        .collision
+        == CollisionShape::Platform
+        || level
+            .moving_platforms
+            .iter()
+            .any(|f| f.carries(pos, level.time()))
+}
+
+/// The surface of the tile the body at `pos` is standing on, if any
+pub fn surface_at(pos: Vec2, level: &Level) -> Surface {
+    let feet = pos + vec2(4.0, 8.0);
+    if !level.collides_at(feet) {
+        return Surface::Normal;
+    }
+    let tile = (feet / 8.0).floor();
+    TILES
+        .get(level.get_tile(tile.x as i16, tile.y as i16)[1])
+        .surface
+}
+
+/// Launches a body standing on a spring upwards, returning whether it was launched
+pub fn bounce_on_spring(pos: Vec2, velocity: &mut Vec2, level: &Level) -> bool {
+    if velocity.y < 0.0 || surface_at(pos, level) != Surface::Spring {
+        return false;
+    }
+    velocity.y = -SPRING_SPEED;
+    true
+}
+
+/// How fast the wind is pushing the body at `pos` sideways
+pub fn wind_at(pos: Vec2, level: &Level) -> f32 {
+    let centre = ((pos + 4.0) / 8.0).floor();
+    match TILES
+        .get(level.get_tile(centre.x as i16, centre.y as i16)[3])
+        .kind
+    {
+        TileKind::Wind(left) => {
+            if left {
+                -WIND_SPEED
+            } else {
+                WIND_SPEED
+            }
+        }
+        _ => 0.0,
+    }
+}
+
+/// The quicksand tile at `point`, if there is one
+fn quicksand_at(point: Vec2, level: &Level) -> Option<u16> {
+    let tile = (point / 8.0).floor();
+    let tile = level.get_tile(tile.x as i16, tile.y as i16)[3];
+    (TILES.get(tile).kind == TileKind::Quicksand).then_some(tile)
+}
+
+/// Finds the highest top of a solid tile on the collision layer at `x`, between `from` and `to`.
+/// Full tiles are only included if `include_full` is set, and platforms only if their top is below `platforms_below`.
+fn find_floor(
+    level: &Level,
+    x: f32,
+    from: f32,
+    to: f32,
+    include_full: bool,
+    platforms_below: Option<f32>,
+) -> Option<f32> {
+    let tx = (x / 8.0).floor();
+    for ty in (from / 8.0).floor() as i16..=(to / 8.0).floor() as i16 {
+        let tile = level.get_tile(tx as i16, ty)[1];
+        let properties = TILES.get(tile);
+        if tile == 0
+            || properties.deadly
+            || (!include_full && properties.collision == CollisionShape::Full)
+        {
+            continue;
+        }
+        let top = ty as f32 * 8.0 + properties.collision.span(x - tx * 8.0).0;
+        if properties.collision == CollisionShape::Platform
+            && platforms_below.is_none_or(|below| top < below)
+        {
+            continue;
+        }
+        if top >= from && top <= to {
+            return Some(top);
+        }
+    }
+    None
+}
+
+/// Where a moving body first hit something
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Contact {
+    /// Points away from what was hit
+    pub normal: Vec2,
+    /// How far through the move the body hit it, from 0.0 to 1.0
+    pub time: f32,
+}
+
+/// Moves a body by its velocity, stopping it at walls, floors and ceilings. Bodies standing on moving platforms
+/// and conveyor belts are carried along, wind pushes them sideways, and quicksand slows them and lets them sink.
+///
+/// Returns the new position, whether it's on the ground, the death animation of a deadly tile it touched,
+/// the direction of a climbable wall it touched, and where it first hit something.
 pub fn update_physicsbody(
+    mut pos: Vec2,
+    velocity: &mut Vec2,
+    delta_time: f32,
+    level: &Level,
+    tall: bool,
+    enable_special_collisions: bool,
+    drop_through: bool,
+) -> (Vec2, bool, Option<usize>, Option<f32>, Option<Contact>) {
+    let start = pos;
+    let time = level.time();
+    let platforms = if drop_through {
+        &[][..]
+    } else {
+        &level.moving_platforms[..]
+    };
+    // bodies standing on a moving platform move along with it, on top of their own velocity.
+    // platforms don't move in frames that take no time, which would otherwise divide by zero
+    let mut carry = platforms
+        .iter()
+        .filter(|_| delta_time > 0.0)
+        .find(|f| f.carries(pos, time - delta_time))
+        .map_or(Vec2::ZERO, |f| {
+            (f.pos_at(time) - f.pos_at(time - delta_time)) / delta_time
+        });
+    if let Surface::Conveyor(left) = surface_at(pos, level) {
+        carry.x += if left {
+            -CONVEYOR_SPEED
+        } else {
+            CONVEYOR_SPEED
+        };
+    }
+    carry.x += wind_at(pos, level);
+    let in_quicksand = quicksand_at(pos + vec2(4.0, 7.0), level).is_some();
+    if in_quicksand {
+        carry.x -= velocity.x * (1.0 - QUICKSAND_SLOWDOWN);
+        velocity.y = velocity.y.min(SINK_SPEED);
+    }
+    *velocity += carry;
+
+    let steps = ((*velocity * delta_time).abs().max_element() / MAX_SUBSTEP)
+        .ceil()
+        .max(1.0);
+    let step_time = delta_time / steps;
+
+    let mut grounded = false;
+    let mut touched_death_tile = None;
+    let mut colliding_with_wall_climb_target = None;
+    let mut contact: Option<Contact> = None;
+    for step in 0..steps as usize {
+        let old_pos = pos;
+        let old_velocity = *velocity;
+        let step_grounded;
+        let (death_tile, wall_climb_target);
+        (pos, step_grounded, death_tile, wall_climb_target) = step_physicsbody(
+            pos,
+            velocity,
+            step_time,
+            level,
+            tall,
+            enable_special_collisions,
+            drop_through,
+        );
+        grounded |= step_grounded;
+        touched_death_tile = death_tile.or(touched_death_tile);
+        colliding_with_wall_climb_target = wall_climb_target.or(colliding_with_wall_climb_target);
+
+        if contact.is_none() {
+            let moved = pos - old_pos;
+            let attempted = old_velocity * step_time;
+            // axes the body was stopped on, as (how far through the step it got, normal)
+            let hits = [
+                (moved.x, attempted.x, Vec2::X, velocity.x),
+                (moved.y, attempted.y, Vec2::Y, velocity.y),
+            ]
+            .into_iter()
+            .filter(|f| f.1 != 0.0 && f.3 == 0.0)
+            .map(|(moved, attempted, axis, _)| {
+                (
+                    (moved / attempted).clamp(0.0, 1.0),
+                    -axis * attempted.signum(),
+                )
+            });
+            contact = hits
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(time, normal)| Contact {
+                    normal,
+                    time: (step as f32 + time) / steps,
+                });
+        }
+        if *velocity == Vec2::ZERO {
+            break;
+        }
+    }
+    if velocity.x != 0.0 {
+        velocity.x -= carry.x;
+    }
+    if velocity.y != 0.0 {
+        velocity.y -= carry.y;
+    }
+
+    // moving platforms are one-way, so only catch bodies that were above them
+    for platform in platforms {
+        let old = platform.pos_at(time - delta_time);
+        let new = platform.pos_at(time);
+        if start.y + 8.0 <= old.y + 1.0
+            && pos.y + 8.0 >= new.y
+            && pos.x + 8.0 > new.x
+            && pos.x < new.x + platform.width()
+        {
+            pos.y = new.y - 8.0;
+            velocity.y = 0.0;
+            grounded = true;
+            touched_death_tile = None;
+            contact.get_or_insert(Contact {
+                normal: vec2(0.0, -1.0),
+                time: 1.0,
+            });
+        }
+    }
+    if in_quicksand {
+        // bodies can jump out of quicksand
+        grounded = true;
+        let head = pos + vec2(4.0, if tall { -8.0 } else { 0.0 });
+        if let Some(tile) = quicksand_at(head, level) {
+            touched_death_tile = Some(TILES.get(tile).death_animation);
+        }
+    }
+    (
+        pos,
+        grounded,
+        touched_death_tile,
+        colliding_with_wall_climb_target,
+        contact,
+    )
+}
+
+fn step_physicsbody(
     pos: Vec2,
     velocity: &mut Vec2,
     delta_time: f32,
     level: &Level,
     tall: bool,
     enable_special_collisions: bool,
-) -> (Vec2, bool, Option<u16>, Option<f32>) {
+    drop_through: bool,
+) -> (Vec2, bool, Option<usize>, Option<f32>) {
     let mut grounded = false;
+    // death animation of the deadly tile that was touched
     let mut touched_death_tile = None;
     let mut new = pos + *velocity * delta_time;
 
+    // bodies walking on slopes and half tiles stand on the surface under their centre.
+    // full tiles within a step of their feet are left for that to handle too, instead of being walls/floors.
+    let on_shape = velocity.y >= 0.0
+        && [pos.x + 4.0, new.x + 4.0].iter().any(|x| {
+            [pos.y + 7.99, pos.y + 8.0 + MAX_STEP].iter().any(|y| {
+                is_shaped(level.get_tile((x / 8.0).floor() as i16, (y / 8.0).floor() as i16)[1])
+            })
+        });
+    let is_step = |ty: f32| on_shape && ty * 8.0 >= pos.y + 8.0 - MAX_STEP;
+
     let tile_x = pos.x / 8.0;
     let tile_y = pos.y / 8.0;
 
@@ -51,15 +451,19 @@ pub fn update_physicsbody(
 
     for (tx, ty) in tiles_y {
         let mut tile = level.get_tile((tx) as i16, (ty) as i16)[1];
-        if !grounded && tile > 0 && DEATH_TILES.contains(&(tile - 1)) {
+        let properties = TILES.get(tile);
+        if !grounded && properties.deadly {
             if ty * 8.0 > pos.y {
-                touched_death_tile = Some(tile - 1);
+                touched_death_tile = Some(properties.death_animation);
             }
             continue;
         }
+        if is_shaped(tile) || (tile != 0 && !properties.deadly && is_step(ty)) {
+            continue;
+        }
         if enable_special_collisions
             && tile == 0
-            && level.get_tile(tx as i16, ty as i16)[3] == 864 + 1
+            && TILES.get(level.get_tile(tx as i16, ty as i16)[3]).solid
         {
             tile = 1;
         }
@@ -91,17 +495,40 @@ pub fn update_physicsbody(
     for (tx, ty) in tiles_x {
         let tile_data = level.get_tile((tx) as i16, (ty) as i16);
         let mut tile = tile_data[1];
-        if tile > 0 && DEATH_TILES.contains(&(tile - 1)) {
+        let properties = TILES.get(tile);
+        if properties.deadly {
             continue;
         }
-        if enable_special_collisions
-            && tile == 0
-            && level.get_tile(tx as i16, ty as i16)[3] == 864 + 1
-        {
+        if properties.collision == CollisionShape::Platform {
+            continue;
+        }
+        if is_shaped(tile) {
+            // only block bodies entering the tile, from the side of it they enter from
+            let entering = if velocity.x > 0.0 {
+                pos.x + 8.0 <= tx * 8.0
+            } else {
+                pos.x >= tx * 8.0 + 8.0
+            };
+            let (top, bottom) = properties
+                .collision
+                .span(if velocity.x > 0.0 { 0.0 } else { 8.0 });
+            let body_top = new.y - if tall { 8.0 } else { 0.0 };
+            let body_bottom = new.y + 8.0;
+            let overlapping = ty * 8.0 + top < body_bottom && ty * 8.0 + bottom > body_top;
+            // low enough to walk onto
+            let floor =
+                ty * 8.0 + bottom >= body_bottom && body_bottom - (ty * 8.0 + top) <= MAX_STEP;
+            if velocity.x == 0.0 || !entering || !overlapping || floor {
+                continue;
+            }
+        } else if tile != 0 && is_step(ty) {
+            continue;
+        }
+        if enable_special_collisions && tile == 0 && TILES.get(tile_data[3]).solid {
             tile = 1;
         }
         if tile != 0 {
-            if tile_data[3] == 512 + 1 || tile_data[3] == 513 + 1 {
+            if TILES.get(tile_data[3]).kind == TileKind::Climbable {
                 if velocity.y < 0.0 {
                     velocity.y = (velocity.y + 125.0 * delta_time).min(0.0);
                 }
@@ -117,6 +544,41 @@ pub fn update_physicsbody(
             break;
         }
     }
+
+    if velocity.y >= 0.0 {
+        let bottom = new.y + 8.0;
+        let reach_up = MAX_STEP.max(new.y - pos.y);
+        // keep bodies on the ground while walking down slopes
+        let reach_down = if on_shape && !grounded {
+            MAX_STEP + (new.x - pos.x).abs()
+        } else {
+            0.0
+        };
+        // platforms only catch bodies that were above them
+        let platforms_below = (!drop_through).then_some(pos.y + 8.0);
+        if let Some(floor) = find_floor(
+            level,
+            new.x + 4.0,
+            bottom - reach_up,
+            bottom + reach_down,
+            on_shape,
+            platforms_below,
+        ) {
+            new.y = floor - 8.0;
+            velocity.y = 0.0;
+            grounded = true;
+            touched_death_tile = None;
+        }
+    } else {
+        let head = vec2(new.x + 4.0, new.y - if tall { 8.0 } else { 0.0 });
+        let tile_pos = (head / 8.0).floor();
+        let tile = level.get_tile(tile_pos.x as i16, tile_pos.y as i16)[1];
+        let collision = TILES.get(tile).collision;
+        if is_shaped(tile) && collision.contains(head - tile_pos * 8.0) {
+            new.y += tile_pos.y * 8.0 + collision.span(head.x - tile_pos.x * 8.0).1 - head.y;
+            velocity.y = 0.0;
+        }
+    }
     (
         new,
         grounded,
@@ -124,3 +586,333 @@ pub fn update_physicsbody(
         colliding_with_wall_climb_target,
     )
 }
+
+#[test]
+fn bodies_walk_smoothly_over_slopes() {
+    use crate::{assets::LevelData, utils::GRAVITY};
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+    // ramps from the test level's floor up to a raised floor, as (tile, column).
+    // the raised floor is on the side the ramp goes up to.
+    let ramps: [(&[(u16, usize)], bool); 4] = [
+        (&[(1858 + 1, 6)], false),
+        (&[(1860 + 1, 5), (1861 + 1, 6)], false),
+        (&[(1859 + 1, 9)], true),
+        (&[(1863 + 1, 9), (1862 + 1, 10)], true),
+    ];
+    for (ramp, raised_left) in ramps {
+        let mut data =
+            LevelData::parse(&crate::assets::test_level(""), "slope.tmx".to_string()).unwrap();
+        for x in 0..16 {
+            let tile = &mut data.data[x + 14 * data.width][1];
+            if let Some((ramp_tile, _)) = ramp.iter().find(|f| f.1 == x) {
+                *tile = *ramp_tile;
+            } else if ramp.iter().all(|f| (x < f.1) == raised_left) {
+                *tile = 1;
+            }
+        }
+        let level = Level::new(data);
+        let (start, speed) = if raised_left {
+            (120.0, -60.0)
+        } else {
+            (8.0, 60.0)
+        };
+        for tall in [false, true] {
+            let mut pos = vec2(start, 112.0);
+            let mut velocity = Vec2::ZERO;
+            for (speed, end) in [(speed, 104.0), (-speed, 112.0)] {
+                for _ in 0..60 {
+                    let old = pos;
+                    velocity.x = speed;
+                    velocity.y += GRAVITY * DELTA_TIME;
+                    let grounded;
+                    (pos, grounded, _, _, _) = update_physicsbody(
+                        pos,
+                        &mut velocity,
+                        DELTA_TIME,
+                        &level,
+                        tall,
+                        false,
+                        false,
+                    );
+                    assert!(grounded, "{ramp:?} {tall}: fell at {pos}");
+                    assert!(
+                        (pos.y - old.y).abs() <= 1.0,
+                        "{ramp:?} {tall}: jumped from {old} to {pos}"
+                    );
+                }
+                assert_eq!(pos.y, end, "{ramp:?} {tall}");
+            }
+        }
+    }
+}
+
+#[test]
+fn platforms_only_catch_bodies_from_above() {
+    use crate::{assets::LevelData, utils::GRAVITY};
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+    let mut data =
+        LevelData::parse(&crate::assets::test_level(""), "platform.tmx".to_string()).unwrap();
+    for x in 0..16 {
+        data.data[x + 14 * data.width][1] = 1;
+        if x < 8 {
+            data.data[x + 10 * data.width][1] = 1900 + 1;
+        }
+    }
+    // a platform at head height of bodies walking on the floor
+    data.data[12 + 13 * data.width][1] = 1900 + 1;
+    let level = Level::new(data);
+    let simulate = |mut pos: Vec2, mut velocity: Vec2, frames: usize, drop_through: bool| {
+        for _ in 0..frames {
+            velocity.y += GRAVITY * DELTA_TIME;
+            (pos, _, _, _, _) = update_physicsbody(
+                pos,
+                &mut velocity,
+                DELTA_TIME,
+                &level,
+                true,
+                false,
+                drop_through,
+            );
+        }
+        pos
+    };
+    // landing from above
+    assert_eq!(simulate(vec2(16.0, 40.0), Vec2::ZERO, 60, false).y, 72.0);
+    // jumping up through it from below
+    assert_eq!(
+        simulate(vec2(16.0, 104.0), vec2(0.0, -300.0), 60, false).y,
+        72.0
+    );
+    // dropping through
+    let pos = simulate(vec2(16.0, 72.0), Vec2::ZERO, 10, true);
+    assert_eq!(simulate(pos, Vec2::ZERO, 60, false).y, 104.0);
+    assert!(on_platform(vec2(16.0, 72.0), &level));
+    // walking past one from the side
+    let pos = simulate(vec2(72.0, 104.0), vec2(60.0, 0.0), 60, false);
+    assert!(pos.x > 100.0 && pos.y == 104.0, "{pos}");
+}
+
+#[test]
+fn fast_bodies_dont_pass_through_walls() {
+    use crate::assets::LevelData;
+    let mut data =
+        LevelData::parse(&crate::assets::test_level(""), "sweep.tmx".to_string()).unwrap();
+    for y in 0..16 {
+        data.data[10 + y * data.width][1] = 1;
+    }
+    for x in 0..10 {
+        data.data[x + 14 * data.width][1] = 1;
+    }
+    let level = Level::new(data);
+
+    // a body going 60 pixels in one frame, into a one tile thick wall 28 pixels away
+    let mut velocity = vec2(3600.0, 0.0);
+    let (pos, _, _, _, contact) = update_physicsbody(
+        vec2(44.0, 40.0),
+        &mut velocity,
+        1.0 / 60.0,
+        &level,
+        true,
+        false,
+        false,
+    );
+    assert_eq!(pos, vec2(72.0, 40.0));
+    assert_eq!(velocity.x, 0.0);
+    let contact = contact.unwrap();
+    assert_eq!(contact.normal, vec2(-1.0, 0.0));
+    assert!((contact.time - 28.0 / 60.0).abs() < 0.001, "{contact:?}");
+
+    // and through the floor
+    let mut velocity = vec2(0.0, 4800.0);
+    let (pos, grounded, _, _, contact) = update_physicsbody(
+        vec2(16.0, 40.0),
+        &mut velocity,
+        1.0 / 60.0,
+        &level,
+        true,
+        false,
+        false,
+    );
+    assert_eq!(pos, vec2(16.0, 104.0));
+    assert!(grounded);
+    assert_eq!(contact.unwrap().normal, vec2(0.0, -1.0));
+}
+
+#[test]
+fn rays_hit_exactly() {
+    use crate::assets::LevelData;
+    let mut data = LevelData::parse(&crate::assets::test_level(""), "ray.tmx".to_string()).unwrap();
+    for y in 0..16 {
+        data.data[10 + y * data.width][1] = 1;
+    }
+    // a 45° slope going up to the right, and a one-way platform
+    data.data[4 + 12 * data.width][1] = 1858 + 1;
+    data.data[6 + 12 * data.width][1] = 1900 + 1;
+    let level = Level::new(data);
+
+    let hit = raycast(
+        vec2(20.0, 20.0),
+        vec2(120.0, 20.0),
+        &level,
+        RayFilter::Solid,
+    )
+    .unwrap();
+    assert_eq!(hit.tile, (10, 2));
+    assert_eq!(hit.point, vec2(80.0, 20.0));
+    assert_eq!(hit.normal, vec2(-1.0, 0.0));
+    assert_eq!(hit.distance, 60.0);
+    assert!(raycast(vec2(20.0, 20.0), vec2(70.0, 20.0), &level, RayFilter::Solid).is_none());
+
+    // straight down onto the slope, 2 pixels into the tile
+    let hit = raycast(
+        vec2(34.0, 60.0),
+        vec2(34.0, 120.0),
+        &level,
+        RayFilter::Solid,
+    )
+    .unwrap();
+    assert_eq!(hit.tile, (4, 12));
+    assert_eq!(hit.point, vec2(34.0, 102.0));
+    let normal = vec2(-1.0, -1.0).normalize();
+    assert!(hit.normal.distance(normal) < 0.001, "{hit:?}");
+
+    // rays go through platforms, and can look for other layers
+    assert!(
+        raycast(
+            vec2(52.0, 90.0),
+            vec2(52.0, 110.0),
+            &level,
+            RayFilter::Solid
+        )
+        .is_none()
+    );
+    let hit = raycast(
+        vec2(52.0, 90.0),
+        vec2(52.0, 110.0),
+        &level,
+        RayFilter::Layer(1),
+    )
+    .unwrap();
+    assert_eq!((hit.point, hit.normal), (vec2(52.0, 96.0), vec2(0.0, -1.0)));
+
+    // a diagonal ray
+    let hit = raycast(vec2(64.0, 30.0), vec2(84.0, 50.0), &level, RayFilter::Solid).unwrap();
+    assert_eq!((hit.tile, hit.point), ((10, 5), vec2(80.0, 46.0)));
+}
+
+#[test]
+fn moving_platforms_carry_bodies() {
+    use crate::{
+        assets::{LevelData, MOVING_PLATFORM_TILES, MovingPlatform},
+        utils::GRAVITY,
+    };
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+    // one going back and forth sideways, and a lift going up and down
+    for path in [
+        [vec2(16.0, 64.0), vec2(80.0, 64.0)],
+        [vec2(16.0, 96.0), vec2(16.0, 32.0)],
+    ] {
+        let mut data =
+            LevelData::parse(&crate::assets::test_level(""), "lift.tmx".to_string()).unwrap();
+        let mut points = vec![path[0]];
+        while points[points.len() - 1] != path[1] {
+            let last = points[points.len() - 1];
+            points.push(last.move_towards(path[1], 8.0));
+        }
+        let way_back: Vec<Vec2> = points[1..points.len() - 1].iter().rev().copied().collect();
+        points.extend(way_back);
+        data.moving_platforms.push(MovingPlatform {
+            path: points,
+            start: 0,
+            speed: 4.0,
+            tiles: MOVING_PLATFORM_TILES.to_vec(),
+            lasso: false,
+            path_index: None,
+        });
+        let level = Level::new(data);
+        let platform = &level.moving_platforms[0];
+
+        let mut pos = path[0] + vec2(4.0, -8.0);
+        let mut velocity = Vec2::ZERO;
+        // a frame that takes no time leaves the body where it is
+        (pos, _, _, _, _) = update_physicsbody(pos, &mut velocity, 0.0, &level, true, false, false);
+        assert_eq!((pos, velocity), (path[0] + vec2(4.0, -8.0), Vec2::ZERO));
+        for _ in 0..240 {
+            level.advance_time(DELTA_TIME);
+            velocity.y += GRAVITY * DELTA_TIME;
+            let grounded;
+            (pos, grounded, _, _, _) =
+                update_physicsbody(pos, &mut velocity, DELTA_TIME, &level, true, false, false);
+            let platform_pos = platform.pos_at(level.time());
+            assert!(grounded, "{path:?}: fell at {pos}");
+            assert!(
+                pos.distance(platform_pos + vec2(4.0, -8.0)) < 0.01,
+                "{path:?}: at {pos}, platform at {platform_pos}"
+            );
+        }
+        // dropping through it
+        for _ in 0..30 {
+            level.advance_time(DELTA_TIME);
+            velocity.y += GRAVITY * DELTA_TIME;
+            (pos, _, _, _, _) =
+                update_physicsbody(pos, &mut velocity, DELTA_TIME, &level, true, false, true);
+        }
+        assert_eq!(pos.y, 112.0, "{path:?}");
+    }
+}
+
+#[test]
+fn tiles_push_and_slow_bodies() {
+    use crate::{assets::LevelData, utils::GRAVITY};
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+    let level_with = |edit: &dyn Fn(&mut LevelData)| {
+        let mut data =
+            LevelData::parse(&crate::assets::test_level(""), "surfaces.tmx".to_string()).unwrap();
+        edit(&mut data);
+        Level::new(data)
+    };
+    let simulate = |level: &Level, mut pos: Vec2, seconds: f32| {
+        let mut velocity = Vec2::ZERO;
+        let mut died = None;
+        for _ in 0..(seconds / DELTA_TIME) as usize {
+            velocity.y += GRAVITY * DELTA_TIME;
+            let death;
+            (pos, _, death, _, _) =
+                update_physicsbody(pos, &mut velocity, DELTA_TIME, level, true, false, false);
+            died = died.or(death);
+            // carried, not sped up
+            assert_eq!(velocity.x, 0.0);
+        }
+        (pos, died)
+    };
+
+    // a conveyor belt going right along the floor
+    let level = level_with(&|data| {
+        for x in 0..16 {
+            data.data[15 * 16 + x][1] = 1889 + 1;
+        }
+    });
+    let (pos, _) = simulate(&level, vec2(40.0, 112.0), 1.0);
+    assert!((pos.x - (40.0 + CONVEYOR_SPEED)).abs() < 1.0, "{pos}");
+
+    // wind blowing left above the floor
+    let level = level_with(&|data| {
+        for x in 0..16 {
+            data.data[14 * 16 + x][3] = 1898 + 1;
+        }
+    });
+    let (pos, _) = simulate(&level, vec2(80.0, 112.0), 1.0);
+    assert!((pos.x - (80.0 - WIND_SPEED)).abs() < 1.0, "{pos}");
+
+    // quicksand two tiles deep, which bodies sink into until they're under
+    let level = level_with(&|data| {
+        for x in 0..16 {
+            data.data[13 * 16 + x][3] = 1899 + 1;
+            data.data[14 * 16 + x][3] = 1899 + 1;
+        }
+    });
+    let (pos, died) = simulate(&level, vec2(40.0, 96.0), 1.0);
+    assert!(pos.y < 104.0 && died.is_none(), "{pos}");
+    let (pos, died) = simulate(&level, pos, 2.0);
+    assert_eq!((pos.y, died), (112.0, Some(0)));
+}
@@ -9,6 +9,8 @@ pub struct Projectile {
     pub friendly: bool,
     /// True when projectile hits an enemy, marker to show that it should be destroyed.
     pub dead: bool,
+    /// How much health the projectile takes from enemies and bosses it hits
+    pub damage: u32,
 }
 #[allow(clippy::match_like_matches_macro)]
 impl Projectile {
@@ -20,6 +22,7 @@ impl Projectile {
             time: 0.0,
             friendly: type_index == 0,
             dead: false,
+            damage: 1,
         }
     }
     pub fn is_ray(&self) -> bool {
@@ -61,6 +64,13 @@ impl Projectile {
             _ => 8.0,
         }
     }
+    /// Whether the projectile destroys breakable tiles within its collision size when it appears
+    pub fn breaks_tiles(&self) -> bool {
+        match &self.type_index {
+            3 => true,
+            _ => false,
+        }
+    }
     pub fn can_kill(&self) -> bool {
         match &self.type_index {
             2 => false,
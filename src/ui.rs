@@ -3,14 +3,15 @@ use macroquad::prelude::*;
 
 pub fn draw_boss_badges(assets: &Assets, amt: f32, mut achieved: u8, active_screen_width: f32) {
     // draw boss badge animation
-    const BOSS_COUNT: u8 = 3;
+    // a badge for each boss fight in the manifest
+    let boss_count = assets.levels.iter().filter(|f| f.boss.is_some()).count() as u8;
     const FADE_IN_TIME: f32 = 0.2;
     const PAUSE_TIME: f32 = 1.0;
     const FLY_OFF_TIME: f32 = 0.2;
 
     let gap = 6.0;
     let padding = 3.0;
-    let width = BOSS_COUNT as f32 * (10.0 + gap) - gap + 2.0 * padding;
+    let width = boss_count as f32 * (10.0 + gap) - gap + 2.0 * padding;
     let height = 12.0;
 
     let animation_time = amt - 1.0;
@@ -59,7 +60,7 @@ pub fn draw_boss_badges(assets: &Assets, amt: f32, mut achieved: u8, active_scre
         );
     }
 
-    for i in 0..BOSS_COUNT {
+    for i in 0..boss_count {
         let color = if i < achieved { WHITE } else { BLACK };
         assets.boss_badges.draw_tile(
             x + padding + i as f32 * (10.0 + gap),
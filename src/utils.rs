@@ -11,7 +11,6 @@ pub const SCREEN_HEIGHT: f32 = 144.0;
 pub const MAX_LASSO_DISTANCE: f32 = 64.0;
 pub const GRAVITY: f32 = 9.8 * 75.0;
 pub const LEVEL_TRANSITION_LENGTH: f32 = 0.5;
-pub const DEATH_TILES: &[u16] = &[128, 352, 288];
 
 pub const FLOOR_PADDING: f32 = 16.0;
 
@@ -109,6 +108,36 @@ pub fn get_input_axis(gamepad_engine: &mut Gamepads) -> Vec2 {
     i
 }
 
+/// Everything the player pressed or held during one frame, so the player can be updated without reading devices directly
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Input {
+    /// Movement direction, with each axis being -1, 0 or 1
+    pub axis: Vec2,
+    pub jump_pressed: bool,
+    pub jump_down: bool,
+    pub lasso_pressed: bool,
+    pub lasso_down: bool,
+    pub shoot_pressed: bool,
+    pub interact_pressed: bool,
+    /// Lets the player fly through walls. Only available in debug builds.
+    pub noclip: bool,
+}
+impl Input {
+    /// Reads this frame's input from the keyboard, mouse and gamepads
+    pub fn read(gamepad_engine: &mut Gamepads) -> Self {
+        Self {
+            axis: get_input_axis(gamepad_engine),
+            jump_pressed: is_jump_pressed(gamepad_engine),
+            jump_down: is_jump_down(gamepad_engine),
+            lasso_pressed: is_lasso_pressed(gamepad_engine),
+            lasso_down: is_lasso_down(gamepad_engine),
+            shoot_pressed: is_shoot_pressed(gamepad_engine),
+            interact_pressed: is_interact_pressed(gamepad_engine),
+            noclip: cfg!(debug_assertions) && is_key_down(KeyCode::LeftShift),
+        }
+    }
+}
+
 pub static SKY_MATERIAL: LazyLock<Material> = LazyLock::new(|| {
     load_material(
         ShaderSource::Glsl {
@@ -154,8 +183,63 @@ pub static BLOOM_MATERIAL: LazyLock<Material> = LazyLock::new(|| {
     .unwrap()
 });
 
+/// Draws fully transparent pixels. Blending is off, so it erases whatever is drawn over.
+pub static ERASE_MATERIAL: LazyLock<Material> = LazyLock::new(|| {
+    load_material(
+        ShaderSource::Glsl {
+            vertex: DEFAULT_VERTEX_SHADER,
+            fragment: ERASE_FRAGMENT,
+        },
+        MaterialParams::default(),
+    )
+    .unwrap()
+});
+
+/// Draws textures in plain white, keeping their transparency. Used to flash things that get hit.
+pub static FLASH_MATERIAL: LazyLock<Material> = LazyLock::new(|| {
+    let pipeline = PipelineParams {
+        color_blend: Some(BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+        )),
+        ..Default::default()
+    };
+    load_material(
+        ShaderSource::Glsl {
+            vertex: DEFAULT_VERTEX_SHADER,
+            fragment: FLASH_FRAGMENT,
+        },
+        MaterialParams {
+            pipeline_params: pipeline,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+});
+
 pub const SKY_FRAGMENT: &str = include_str!("sky.frag");
 
+pub const ERASE_FRAGMENT: &str = "#version 100
+precision lowp float;
+
+void main() {
+    gl_FragColor = vec4(0.0);
+}
+";
+
+pub const FLASH_FRAGMENT: &str = "#version 100
+precision lowp float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = vec4(1.0, 1.0, 1.0, texture2D(Texture, uv).a);
+}
+";
+
 pub const DEFAULT_VERTEX_SHADER: &str = "#version 100
 precision lowp float;
 